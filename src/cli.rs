@@ -1,6 +1,12 @@
-use crate::{utility::PinFuture, Config, Runtime};
+use crate::{utility::PinFuture, Runtime, Session};
 use clap::{FromArgMatches, Parser};
 
+mod doctor;
+use doctor::*;
+mod logs;
+use logs::*;
+mod profile;
+use profile::*;
 mod release_binary;
 use release_binary::*;
 mod save_config;
@@ -10,18 +16,18 @@ use update_config::*;
 
 pub struct Cli;
 impl crate::Plugin for Cli {
-	fn add_subcommands(&self, runtime: &mut Runtime, _config: &Config) {
+	fn add_subcommands(&self, runtime: &mut Runtime, _session: &Session) {
 		runtime.augment_subcommands::<Commands>();
 	}
 
 	fn run(
 		&self,
 		matches: &clap::ArgMatches,
-		config: &mut Option<Config>,
+		session: &Session,
 	) -> Option<PinFuture<anyhow::Result<()>>> {
 		use crate::Operation;
 		if let Ok(cmds) = Commands::from_arg_matches(matches) {
-			return Some(cmds.run(config.take().unwrap()));
+			return Some(cmds.run(session.clone()));
 		}
 		None
 	}
@@ -31,17 +37,22 @@ impl crate::Plugin for Cli {
 pub enum Commands {
 	InitCfg(SaveToDisk),
 	Cfg(Configure),
+	Doctor(Doctor),
+	#[command(subcommand)]
+	Logs(Logs),
 	#[cfg(debug_assertions)]
 	Ship(ReleaseBinary),
 }
 
 impl crate::Operation for Commands {
-	fn run(self, config: Config) -> PinFuture<anyhow::Result<()>> {
+	fn run(self, session: Session) -> PinFuture<anyhow::Result<()>> {
 		match self {
-			Self::InitCfg(cmd) => cmd.run(config),
-			Self::Cfg(cmd) => cmd.run(config),
+			Self::InitCfg(cmd) => cmd.run(session),
+			Self::Cfg(cmd) => cmd.run(session),
+			Self::Doctor(cmd) => cmd.run(session),
+			Self::Logs(cmd) => cmd.run(session),
 			#[cfg(debug_assertions)]
-			Self::Ship(cmd) => cmd.run(config),
+			Self::Ship(cmd) => cmd.run(session),
 		}
 	}
 }
@@ -0,0 +1,404 @@
+use crate::{
+	config::{Config, Key},
+	output::{emit_data, OutputFormat},
+	run_log::list_run_logs,
+	types::{HostPlatform, Target},
+	unreal::UProject,
+};
+use clap::Parser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Check that the config, project and tools needed to build are valid.
+///
+/// Each check passes, warns (something may not work as expected) or fails (builds will not work),
+/// and provides a hint for how to fix it.
+#[derive(Parser, Debug)]
+pub struct Doctor {
+	/// Print the results as a JSON `data` event, the same as `--output json`.
+	#[clap(long)]
+	json: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+	Pass,
+	Warn,
+	Fail,
+}
+
+impl std::fmt::Display for CheckStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Pass => write!(f, "pass"),
+			Self::Warn => write!(f, "WARN"),
+			Self::Fail => write!(f, "FAIL"),
+		}
+	}
+}
+
+/// The result of a single doctor check.
+#[derive(Clone, Debug, Serialize)]
+pub struct Check {
+	pub name: String,
+	pub status: CheckStatus,
+	pub message: String,
+	/// How to fix the problem, if the check did not pass.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub hint: Option<String>,
+}
+
+impl Check {
+	fn pass(name: impl Into<String>, message: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			status: CheckStatus::Pass,
+			message: message.into(),
+			hint: None,
+		}
+	}
+
+	fn warn(name: impl Into<String>, message: impl Into<String>, hint: impl Into<String>) -> Self {
+		Self {
+			status: CheckStatus::Warn,
+			hint: Some(hint.into()),
+			..Self::pass(name, message)
+		}
+	}
+
+	fn fail(name: impl Into<String>, message: impl Into<String>, hint: impl Into<String>) -> Self {
+		Self {
+			status: CheckStatus::Fail,
+			hint: Some(hint.into()),
+			..Self::pass(name, message)
+		}
+	}
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+	checks: &'a Vec<Check>,
+	passed: usize,
+	warnings: usize,
+	failures: usize,
+}
+
+impl crate::Operation for Doctor {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let mut checks = Vec::new();
+			checks.push(check_engine_path(config));
+			checks.push(check_editor_binary(config));
+			checks.push(check_project_root(config));
+			checks.push(check_uproject(config).await);
+			for (key, target) in [
+				(Key::ProjectEditorTarget, Target::Editor),
+				(Key::ProjectClientTarget, Target::Client),
+				(Key::ProjectServerTarget, Target::Server),
+			] {
+				checks.push(check_target(config, key, target));
+			}
+			checks.push(check_log_retention(config));
+			for category in ["Engine", "Game"] {
+				checks.push(check_ini(config, category));
+			}
+			checks.push(check_tool(
+				"p4",
+				"Perforce is used to check out binaries when fixing them up",
+			));
+			checks.push(check_tool(
+				"dotnet",
+				"UnrealBuildTool and AutomationTool run on .NET",
+			));
+
+			let count = |status| checks.iter().filter(|check| check.status == status).count();
+			let report = Report {
+				checks: &checks,
+				passed: count(CheckStatus::Pass),
+				warnings: count(CheckStatus::Warn),
+				failures: count(CheckStatus::Fail),
+			};
+			match (session.output(), self.json) {
+				(OutputFormat::Json, _) | (OutputFormat::Text, true) => emit_data(&report)?,
+				(OutputFormat::Text, false) => {
+					for check in checks.iter() {
+						println!("[{}] {}: {}", check.status, check.name, check.message);
+						if let Some(hint) = &check.hint {
+							println!("       hint: {hint}");
+						}
+					}
+					println!(
+						"{} passed, {} warnings, {} failed",
+						report.passed, report.warnings, report.failures
+					);
+				}
+			}
+			if report.failures > 0 {
+				return Err(ChecksFailed(report.failures))?;
+			}
+			Ok(())
+		})
+	}
+}
+
+fn set_hint(key: Key, example: &str) -> String {
+	format!(
+		"run `uebuild cfg {} {example}`, or set {}",
+		key.name(),
+		key.env_var()
+	)
+}
+
+fn check_engine_path(config: &Config) -> Check {
+	let name = Key::EnginePath.name();
+	let path = config.engine_path();
+	let hint = set_hint(Key::EnginePath, "<path to the Engine directory>");
+	if path.as_os_str().is_empty() {
+		return Check::fail(name, "not set", hint);
+	}
+	if !path.is_dir() {
+		return Check::fail(name, format!("{} does not exist", path.display()), hint);
+	}
+	let batch_files = path.join("Build/BatchFiles");
+	if !batch_files.is_dir() {
+		return Check::fail(
+			name,
+			format!("{} does not contain Build/BatchFiles", path.display()),
+			format!("engine-path should be the engine's `Engine` directory; {hint}"),
+		);
+	}
+	let build_script = path.join(HostPlatform::current().build_script());
+	if !build_script.exists() {
+		return Check::fail(
+			name,
+			format!("{} does not exist", build_script.display()),
+			"the engine install is incomplete; run Setup and GenerateProjectFiles for source builds, or verify the install in the launcher",
+		);
+	}
+	match config.engine_version() {
+		Some(version) => Check::pass(name, format!("{} ({version})", path.display())),
+		None => Check::pass(name, format!("{}", path.display())),
+	}
+}
+
+fn check_editor_binary(config: &Config) -> Check {
+	let name = Key::EditorBinaryPath.name();
+	let hint = format!(
+		"build the engine's editor, or {}",
+		set_hint(Key::EditorBinaryPath, "<path relative to engine-path>")
+	);
+	if config
+		.get(&Key::EditorBinaryPath)
+		.is_none_or(|path| path.is_empty())
+	{
+		return Check::fail(name, "not set", hint);
+	}
+	let path = config.editor_binary();
+	if !path.is_file() {
+		return Check::fail(name, format!("{} does not exist", path.display()), hint);
+	}
+	if !is_executable(&path) {
+		return Check::fail(
+			name,
+			format!("{} is not executable", path.display()),
+			format!("run `chmod +x {}`", path.display()),
+		);
+	}
+	Check::pass(name, format!("{}", path.display()))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+	use std::os::unix::fs::PermissionsExt;
+	match path.metadata() {
+		Ok(metadata) => metadata.permissions().mode() & 0o111 != 0,
+		Err(_) => false,
+	}
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+	path.is_file()
+}
+
+fn check_project_root(config: &Config) -> Check {
+	let name = Key::ProjectRoot.name();
+	let path = config.project_root();
+	let hint = "run uebuild from within the project directory, or pass --project <path>";
+	if path.as_os_str().is_empty() {
+		return Check::fail(name, "not set", hint);
+	}
+	if !path.is_dir() {
+		return Check::fail(name, format!("{} does not exist", path.display()), hint);
+	}
+	Check::pass(name, format!("{}", path.display()))
+}
+
+async fn check_uproject(config: &Config) -> Check {
+	let name = Key::ProjectName.name();
+	let path = config.uproject_path();
+	if config.project_name().is_empty() {
+		return Check::fail(
+			name,
+			"not set",
+			set_hint(Key::ProjectName, "<uproject name>"),
+		);
+	}
+	if !path.is_file() {
+		return Check::fail(
+			name,
+			format!("{} does not exist", path.display()),
+			set_hint(Key::ProjectName, "<uproject name>"),
+		);
+	}
+	match UProject::read(&path).await {
+		Ok(_) => Check::pass(name, format!("{}", path.display())),
+		Err(err) => Check::fail(
+			name,
+			format!("{} could not be parsed: {err:#}", path.display()),
+			"fix the uproject's JSON, or regenerate it by opening the project in the editor",
+		),
+	}
+}
+
+fn check_target(config: &Config, key: Key, target: Target) -> Check {
+	let name = key.name();
+	let Some(target_name) = config.get_project_target(target) else {
+		let article = match target {
+			Target::Editor => "an",
+			Target::Client | Target::Server => "a",
+		};
+		return Check::warn(
+			name,
+			"not set",
+			format!(
+				"add {article} {target} target to Source/, or {}",
+				set_hint(key, "<target name>")
+			),
+		);
+	};
+	match config
+		.targets()
+		.iter()
+		.find(|rules| &rules.name == target_name)
+	{
+		Some(rules) => Check::pass(name, format!("{target_name} ({})", rules.path.display())),
+		None => Check::fail(
+			name,
+			format!("no Source/{target_name}.Target.cs"),
+			format!(
+				"add the target to Source/, or {}",
+				set_hint(key, "<target name>")
+			),
+		),
+	}
+}
+
+fn check_log_retention(config: &Config) -> Check {
+	let name = Key::LogRetention.name();
+	let dir = config.run_logs_dir();
+	let logs = match list_run_logs(&dir) {
+		Ok(logs) => logs,
+		Err(err) => {
+			return Check::warn(
+				name,
+				format!("{err:#}"),
+				format!("make sure {} is a readable directory", dir.display()),
+			)
+		}
+	};
+	match config.log_retention() {
+		0 => Check::warn(
+			name,
+			format!(
+				"0, so every run log is kept ({} in {})",
+				logs.len(),
+				dir.display()
+			),
+			format!(
+				"to delete old logs automatically, {}",
+				set_hint(Key::LogRetention, "<number of logs>")
+			),
+		),
+		retention => Check::pass(
+			name,
+			format!("{retention} ({} in {})", logs.len(), dir.display()),
+		),
+	}
+}
+
+fn check_ini(config: &Config, category: &str) -> Check {
+	let name = format!("Default{category}.ini");
+	let path = config.ini_locations().project_file(category, None);
+	match path.is_file() {
+		true => Check::pass(name, format!("{}", path.display())),
+		false => Check::warn(
+			name,
+			format!("{} does not exist", path.display()),
+			"open the project in the editor to create it, or restore it from source control",
+		),
+	}
+}
+
+fn check_tool(name: &str, purpose: &str) -> Check {
+	match find_in_path(name) {
+		Some(path) => Check::pass(name, format!("{}", path.display())),
+		None => Check::warn(
+			name,
+			format!("not found on PATH ({purpose})"),
+			format!("install {name}, or add its directory to PATH"),
+		),
+	}
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+	let file_name = HostPlatform::current().executable_name(name);
+	let paths = std::env::var_os("PATH")?;
+	std::env::split_paths(&paths)
+		.map(|dir| dir.join(&file_name))
+		.find(|path| path.is_file())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct ChecksFailed(usize);
+impl std::fmt::Display for ChecksFailed {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} doctor checks failed", self.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn empty_editor_binary_path_is_not_set() {
+		let check = check_editor_binary(&Config::default());
+		assert_eq!(check.status, CheckStatus::Fail);
+		assert_eq!(check.message, "not set");
+	}
+
+	#[test]
+	fn missing_targets_are_named_with_an_article() {
+		let config = Config::default();
+		let check = check_target(&config, Key::ProjectEditorTarget, Target::Editor);
+		assert!(check.hint.unwrap().starts_with("add an editor target"));
+		let check = check_target(&config, Key::ProjectServerTarget, Target::Server);
+		assert!(check.hint.unwrap().starts_with("add a server target"));
+	}
+
+	#[test]
+	fn keeping_every_log_is_a_warning() {
+		let mut config = Config::default();
+		let check = check_log_retention(&config);
+		assert_eq!(check.status, CheckStatus::Pass);
+		assert!(check.message.starts_with("20 (0 in "));
+
+		config.set(&Key::LogRetention, "0".to_owned());
+		let check = check_log_retention(&config);
+		assert_eq!(check.status, CheckStatus::Warn);
+		assert!(check.message.starts_with("0, so every run log is kept"));
+	}
+}
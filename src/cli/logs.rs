@@ -0,0 +1,163 @@
+use crate::{
+	config::Config,
+	output::{emit_data, OutputFormat},
+	run_log::{list_run_logs, RunLogInfo},
+};
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// Browse the logs that each uebuild run writes to `Saved/Logs/uebuild`.
+///
+/// Logs record the command line, environment, start and end times, exit status,
+/// and the timestamped output of every process that was run.
+/// The number of logs kept is set by `log-retention` (`0` keeps every log).
+#[derive(Subcommand, Debug)]
+pub enum Logs {
+	List(ListLogs),
+	Show(ShowLog),
+	Last(ShowLastLog),
+}
+
+impl crate::Operation for Logs {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		match self {
+			Self::List(cmd) => cmd.run(session),
+			Self::Show(cmd) => cmd.run(session),
+			Self::Last(cmd) => cmd.run(session),
+		}
+	}
+}
+
+/// List the run logs, most recent first.
+#[derive(Parser, Debug)]
+pub struct ListLogs;
+
+impl crate::Operation for ListLogs {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let logs = run_logs(session.config())?;
+			if session.output() == OutputFormat::Json {
+				let mut entries = Vec::new();
+				for (index, path) in logs.iter().enumerate() {
+					let info = RunLogInfo::read(path)?;
+					entries.push(json!({
+						"number": index + 1,
+						"path": path,
+						"size": path.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+						"command_line": info.command_line,
+						"started": info.started.map(|time| time.to_rfc3339()),
+						"exit_status": info.exit_status,
+					}));
+				}
+				return emit_data(&entries);
+			}
+			if logs.is_empty() {
+				println!("No logs in {}", session.config().run_logs_dir().display());
+				return Ok(());
+			}
+			for (index, path) in logs.iter().enumerate() {
+				let info = RunLogInfo::read(path)?;
+				let name = path.file_name().unwrap().to_string_lossy();
+				let size = path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+				let status = match info.exit_status {
+					Some(code) => format!("exit {code}"),
+					None => "unfinished".to_owned(),
+				};
+				println!(
+					"{:>3}  {name}  {:>9}  {status:<10}  {}",
+					index + 1,
+					format_size(size),
+					info.command_line.unwrap_or_default()
+				);
+			}
+			Ok(())
+		})
+	}
+}
+
+/// Print a run log.
+#[derive(Parser, Debug)]
+pub struct ShowLog {
+	/// The file name of the log, or its number in `logs list` (`1` is the most recent).
+	log: String,
+}
+
+impl crate::Operation for ShowLog {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let logs = run_logs(session.config())?;
+			let path = match self.log.parse::<usize>() {
+				Ok(index) => logs.get(index.wrapping_sub(1)),
+				Err(_) => logs
+					.iter()
+					.find(|path| path.file_name().is_some_and(|name| *name == *self.log)),
+			};
+			let Some(path) = path else {
+				return Err(LogNotFound(self.log))?;
+			};
+			print_log(path, session.output())
+		})
+	}
+}
+
+/// Print the most recent run log.
+#[derive(Parser, Debug)]
+pub struct ShowLastLog;
+
+impl crate::Operation for ShowLastLog {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let logs = run_logs(session.config())?;
+			let Some(path) = logs.first() else {
+				return Err(LogNotFound("last".to_owned()))?;
+			};
+			print_log(path, session.output())
+		})
+	}
+}
+
+/// The run logs of the project, most recent first.
+fn run_logs(config: &Config) -> anyhow::Result<Vec<PathBuf>> {
+	let mut logs = list_run_logs(&config.run_logs_dir())?;
+	logs.reverse();
+	Ok(logs)
+}
+
+/// Prints the log, or emits it with its summary as a data event for `--output json`.
+fn print_log(path: &PathBuf, output: OutputFormat) -> anyhow::Result<()> {
+	let content = std::fs::read_to_string(path)?;
+	if output == OutputFormat::Json {
+		let info = RunLogInfo::read(path)?;
+		return emit_data(&json!({
+			"path": path,
+			"command_line": info.command_line,
+			"started": info.started.map(|time| time.to_rfc3339()),
+			"exit_status": info.exit_status,
+			"content": content,
+		}));
+	}
+	println!("{}", path.display());
+	print!("{content}");
+	Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+	match bytes {
+		0..1024 => format!("{bytes} B"),
+		1024..1048576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+		_ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct LogNotFound(String);
+impl std::fmt::Display for LogNotFound {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"no run log matches {:?}, see `uebuild logs list`",
+			self.0
+		)
+	}
+}
@@ -0,0 +1,175 @@
+use crate::{
+	config::{parse_override, BuildDefaults, Key, Profile},
+	output::{emit_data, OutputFormat},
+	types::{Configuration, Platform, Target, TargetSelector},
+};
+use clap::{Parser, Subcommand};
+
+/// Manage the named profiles in the project config.
+///
+/// A profile holds config values which override the project config,
+/// and defaults for `compile` and `cook` options.
+/// Profiles are selected with `--profile <name>`, `UEBUILD_PROFILE`, or `cfg profile use <name>`.
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+	List(ListProfiles),
+	Create(CreateProfile),
+	Use(UseProfile),
+	Delete(DeleteProfile),
+}
+
+impl crate::Operation for ProfileCommand {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		match self {
+			Self::List(cmd) => cmd.run(session),
+			Self::Create(cmd) => cmd.run(session),
+			Self::Use(cmd) => cmd.run(session),
+			Self::Delete(cmd) => cmd.run(session),
+		}
+	}
+}
+
+/// List the profiles, their values and command defaults.
+#[derive(Parser, Debug)]
+pub struct ListProfiles;
+
+impl crate::Operation for ListProfiles {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			if session.output() == OutputFormat::Json {
+				let profiles = config
+					.profiles()
+					.iter()
+					.map(|(name, profile)| {
+						serde_json::json!({
+							"name": name,
+							"active": config.active_profile_name() == Some(name),
+							"default": config.default_profile() == Some(name),
+							"values": profile.values,
+							"compile": profile.compile,
+							"cook": profile.cook,
+						})
+					})
+					.collect::<Vec<_>>();
+				return emit_data(&profiles);
+			}
+			if config.profiles().is_empty() {
+				println!("No profiles, create one with `cfg profile create <name>`");
+				return Ok(());
+			}
+			for (name, profile) in config.profiles().iter() {
+				let mut tags = Vec::new();
+				if config.active_profile_name() == Some(name) {
+					tags.push("active");
+				}
+				if config.default_profile() == Some(name) {
+					tags.push("default");
+				}
+				match tags.is_empty() {
+					true => println!("{name}"),
+					false => println!("{name} ({})", tags.join(", ")),
+				}
+				for (key, value) in profile.values.iter() {
+					println!("  {} = {value:?}", key.name());
+				}
+				if !profile.compile.is_empty() {
+					println!("  compile: {}", profile.compile);
+				}
+				if !profile.cook.is_empty() {
+					println!("  cook: {}", profile.cook);
+				}
+			}
+			Ok(())
+		})
+	}
+}
+
+/// Create a profile, replacing any existing profile with the same name.
+#[derive(Parser, Debug)]
+pub struct CreateProfile {
+	name: String,
+	/// Config values for the profile (e.g. `engine-path=/opt/UE_5.3`).
+	#[clap(value_name = "KEY=VALUE", value_parser = parse_override)]
+	values: Vec<(Key, String)>,
+	/// The default `compile --target`.
+	#[clap(long)]
+	compile_target: Option<TargetSelector>,
+	/// The default `compile --platform`.
+	#[clap(long, value_enum)]
+	compile_platform: Option<Platform>,
+	/// The default `compile --configuration`.
+	#[clap(long, value_enum)]
+	compile_configuration: Option<Configuration>,
+	/// The default `cook --target`.
+	#[clap(long, value_enum)]
+	cook_target: Option<Target>,
+	/// The default `cook --platform`.
+	#[clap(long, value_enum)]
+	cook_platform: Option<Platform>,
+	/// The default `cook --configuration`.
+	#[clap(long, value_enum)]
+	cook_configuration: Option<Configuration>,
+}
+
+impl crate::Operation for CreateProfile {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let mut config = session.config().clone();
+			let profile = Profile {
+				values: self.values.into_iter().collect(),
+				compile: BuildDefaults {
+					target: self.compile_target,
+					platform: self.compile_platform,
+					configuration: self.compile_configuration,
+				},
+				cook: BuildDefaults {
+					target: self.cook_target,
+					platform: self.cook_platform,
+					configuration: self.cook_configuration,
+				},
+			};
+			if config.profiles().contains_key(&self.name) {
+				tracing::info!("Replacing profile {:?}", self.name);
+			}
+			config.insert_profile(self.name, profile);
+			config.save(session.runner()).await?;
+			Ok(())
+		})
+	}
+}
+
+/// Set the profile used when none is selected by `--profile` or `UEBUILD_PROFILE`.
+#[derive(Parser, Debug)]
+pub struct UseProfile {
+	/// The profile to use by default. Omit to stop using a default profile.
+	name: Option<String>,
+}
+
+impl crate::Operation for UseProfile {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let mut config = session.config().clone();
+			config.set_default_profile(self.name)?;
+			config.save(session.runner()).await?;
+			Ok(())
+		})
+	}
+}
+
+/// Delete a profile.
+#[derive(Parser, Debug)]
+pub struct DeleteProfile {
+	name: String,
+}
+
+impl crate::Operation for DeleteProfile {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let mut config = session.config().clone();
+			config.remove_profile(&self.name)?;
+			config.save(session.runner()).await?;
+			Ok(())
+		})
+	}
+}
@@ -1,4 +1,4 @@
-use crate::utility::{spawn_command, PinFuture};
+use crate::types::HostPlatform;
 use clap::Parser;
 use tokio::process::Command;
 
@@ -10,25 +10,33 @@ pub struct ReleaseBinary {
 }
 
 impl crate::Operation for ReleaseBinary {
-	fn run(self, config: crate::config::Config) -> PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			let out_path = config.project_root().join(self.name).with_extension("exe");
+			let config = session.config();
+			let runner = session.runner();
+			let host = HostPlatform::current();
+			let out_path = config.project_root().join(host.executable_name(&self.name));
 
 			let cwd = std::env::current_dir()?;
-			spawn_command(
-				Command::new("cargo")
-					.args(&["build", "--release"])
-					.current_dir(cwd.clone()),
-			)
-			.await?;
+			runner
+				.spawn(
+					Command::new("cargo")
+						.args(["build", "--release"])
+						.current_dir(cwd.clone()),
+				)
+				.await?;
 
-			spawn_command(
-				Command::new("cp")
-					.arg("./target/release/uebuild.exe")
-					.arg(format!("{}", out_path.display()))
-					.current_dir(cwd.clone()),
-			)
-			.await?;
+			runner
+				.spawn(
+					Command::new("cp")
+						.arg(format!(
+							"./target/release/{}",
+							host.executable_name("uebuild")
+						))
+						.arg(format!("{}", out_path.display()))
+						.current_dir(cwd.clone()),
+				)
+				.await?;
 
 			Ok(())
 		})
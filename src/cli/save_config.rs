@@ -1,14 +1,15 @@
-use crate::Config;
 use clap::Parser;
 
 /// Save the dynamically generated config to the current directory.
+///
+/// The config is always written with the current schema version.
 #[derive(Parser, Debug)]
 pub struct SaveToDisk;
 
 impl crate::Operation for SaveToDisk {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			config.save().await?;
+			session.config().save(session.runner()).await?;
 			Ok(())
 		})
 	}
@@ -1,5 +1,11 @@
-use crate::{config::Key, types::Target, Config};
-use clap::Parser;
+use super::ProfileCommand;
+use crate::{
+	config::{Config, Key},
+	output::{emit_data, OutputFormat},
+	types::Target,
+};
+use clap::{Parser, Subcommand};
+use serde_json::json;
 
 /// Handle changes to the user preferences/configuration for this project.
 ///
@@ -8,20 +14,65 @@ use clap::Parser;
 /// With key but no value with present the value for that key.
 ///
 /// With key and value will update the config with the value.
+///
+/// Values are layered, from lowest to highest precedence:
+/// values generated from the project on disk, the per-user `~/.config/uebuild/config.toml`,
+/// the project's `{exe}-cfg.json`, `UEBUILD_*` environment variables, and `--set key=value`.
+/// Only the project config is updated when setting a value.
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Configure {
+	#[command(subcommand)]
+	command: Option<ConfigureCommand>,
 	#[clap(value_enum)]
 	key: Option<Key>,
 	value: Option<String>,
+	/// Print which layer each value was taken from instead of the full configuration.
+	#[clap(long, conflicts_with = "value")]
+	show_origin: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigureCommand {
+	#[command(subcommand)]
+	Profile(ProfileCommand),
 }
 
 impl crate::Operation for Configure {
-	fn run(self, mut config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		if let Some(ConfigureCommand::Profile(cmd)) = self.command {
+			return cmd.run(session);
+		}
 		Box::pin(async move {
+			let mut config = session.config().clone();
+			let runner = session.runner();
+			let json = session.output() == OutputFormat::Json;
 			match (self.key, self.value) {
+				(None, _) if json && self.show_origin => {
+					let values = enumset::EnumSet::<Key>::all()
+						.iter()
+						.map(|key| value_document(&config, key))
+						.collect::<Vec<_>>();
+					emit_data(&values)?;
+				}
+				(None, _) if json => emit_data(&config_document(&config))?,
+				(Some(key), None) if json => emit_data(&value_document(&config, key))?,
+				(None, _) if self.show_origin => {
+					for key in enumset::EnumSet::<Key>::all().iter() {
+						let value = config.get(&key).unwrap_or_default();
+						match config.origin(&key) {
+							Some(origin) => println!("{} = {value:?} ({origin})", key.name()),
+							None => println!("{} = {value:?} (unset)", key.name()),
+						}
+					}
+				}
 				(None, _) => {
 					println!("Project:");
 					println!("  Root: {:?}", config.project_root());
+					match config.active_profile_name() {
+						Some(name) => println!("  Profile: {name:?}"),
+						None => println!("  Profile: none"),
+					}
 					println!("  Name: {:?}", config.project_name());
 					println!("  UProject: {:?}", config.uproject_path());
 					println!("  Targets:");
@@ -37,37 +88,111 @@ impl crate::Operation for Configure {
 						"    Server: {:?}",
 						config.get_project_target(Target::Server)
 					);
+					println!("    Discovered:");
+					for target in config.targets().iter() {
+						println!("      {} ({})", target.name, target.target_type);
+					}
 					println!("Engine:");
 					println!("  Path: {:?}", config.engine_path());
+					match config.engine_version() {
+						Some(version) => println!("  Version: {version}"),
+						None => println!("  Version: unknown"),
+					}
 					println!("  Default Maps:");
 					println!("    Server: {:?}", config.engine().default_map_server());
 					println!("    Game: {:?}", config.engine().default_map_game());
-					println!("  Modes: {}", config.engine().mode_aliases().join(", "));
+					println!("  Modes:");
+					for alias in config.engine().game_mode_aliases().iter() {
+						println!("    {} => {}", alias.name, alias.game_mode);
+					}
 					println!("Game:");
 					println!("  Maps:");
-					{
-						let maps_by_name = config.game().maps_by_name();
-						let maps = {
-							let mut maps = maps_by_name.iter().collect::<Vec<_>>();
-							maps.sort_by_key(|(name, _)| *name);
-							maps
+					for (name, map) in config.game().maps_by_name().into_iter() {
+						let cooked = match map.cooked {
+							true => " (cooked)",
+							false => "",
 						};
-						for (name, path) in maps.into_iter() {
-							println!("    {:?} => {:?}", name, path);
-						}
+						println!("    {:?} => {:?}{cooked}", name, map.package);
 					}
 					println!("Editor:");
 					println!("  Binary Path: {:?}", config.editor_binary());
 				}
-				(Some(key), None) => {
-					println!("{:?} => {:?}", key, config.get(&key));
-				}
+				(Some(key), None) => match config.origin(&key).filter(|_| self.show_origin) {
+					Some(origin) => println!("{:?} => {:?} ({origin})", key, config.get(&key)),
+					None => println!("{:?} => {:?}", key, config.get(&key)),
+				},
 				(Some(key), Some(value)) => {
 					config.set(&key, value);
-					config.save().await?;
+					config.save(runner).await?;
 				}
 			}
 			Ok(())
 		})
 	}
 }
+
+/// The value of a key and the layer it was taken from, for `--output json`.
+fn value_document(config: &Config, key: Key) -> serde_json::Value {
+	json!({
+		"key": key.name(),
+		"value": config.get(&key),
+		"origin": config.origin(&key).map(ToString::to_string),
+	})
+}
+
+/// The full configuration and what was discovered about the project, for `--output json`.
+fn config_document(config: &Config) -> serde_json::Value {
+	let targets = config
+		.targets()
+		.iter()
+		.map(|target| {
+			json!({
+				"name": target.name,
+				"type": target.target_type.to_string(),
+				"path": target.path,
+			})
+		})
+		.collect::<Vec<_>>();
+	let modes = config
+		.engine()
+		.game_mode_aliases()
+		.iter()
+		.map(|alias| json!({ "name": alias.name, "game_mode": alias.game_mode }))
+		.collect::<Vec<_>>();
+	let maps = config
+		.game()
+		.maps_by_name()
+		.into_iter()
+		.map(|(name, map)| json!({ "name": name, "package": map.package, "cooked": map.cooked }))
+		.collect::<Vec<_>>();
+	json!({
+		"project": {
+			"root": config.project_root(),
+			"profile": config.active_profile_name(),
+			"name": config.project_name(),
+			"uproject": config.uproject_path(),
+			"targets": {
+				"editor": config.get_project_target(Target::Editor),
+				"client": config.get_project_target(Target::Client),
+				"server": config.get_project_target(Target::Server),
+				"discovered": targets,
+			},
+		},
+		"engine": {
+			"path": config.engine_path(),
+			"version": config.engine_version().map(ToString::to_string),
+			"default_maps": {
+				"server": config.engine().default_map_server(),
+				"game": config.engine().default_map_game(),
+			},
+			"modes": modes,
+		},
+		"game": {
+			"maps": maps,
+		},
+		"editor": {
+			"binary_path": config.editor_binary(),
+		},
+		"log_retention": config.log_retention(),
+	})
+}
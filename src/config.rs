@@ -1,19 +1,35 @@
 use crate::{
-	types::Target,
-	unreal::{self, EditorTarget, UProject},
+	runner::SharedRunner,
+	types::{HostPlatform, Platform, Target, TargetSelector},
+	unreal::{
+		self,
+		ini::{IniHierarchy, IniLocations, IniValue},
+		DiscoveredPlugin, EditorTarget, EngineRegistry, EngineVersion, MapAsset, TargetRules,
+		TargetType, UProject, UE4_EDITOR_NAME,
+	},
+	utility::AsUnrealStr,
 };
 use anyhow::Context;
 use clap::ValueEnum;
 use enumset::EnumSetType;
 use serde::{Deserialize, Serialize};
 use std::{
-	collections::{HashMap, HashSet},
+	collections::{BTreeMap, HashMap, HashSet},
 	path::{Path, PathBuf},
 	str::FromStr,
 };
 
+mod discovery;
+pub use discovery::*;
+mod layers;
+pub use layers::*;
+mod profile;
+pub use profile::*;
+mod schema;
+pub use schema::{UnsupportedConfigVersion, CONFIG_VERSION};
+
 /// An identifier for all supported per-user settings/preferences.
-#[derive(Debug, PartialOrd, Ord, ValueEnum, Serialize, Deserialize, EnumSetType)]
+#[derive(Debug, PartialOrd, Ord, Hash, ValueEnum, Serialize, Deserialize, EnumSetType)]
 pub enum Key {
 	/// The aboslute path to the engine.
 	/// Should be equivalent to "/c/Program Files/Epic Games/UE4.26/".
@@ -31,6 +47,9 @@ pub enum Key {
 	ProjectClientTarget,
 	/// The name of the `.target.cs` file associated with building a dedicated server.
 	ProjectServerTarget,
+	/// The number of run logs to keep in `Saved/Logs/uebuild/`, where older logs are deleted.
+	/// `0` keeps every log. Defaults to 20.
+	LogRetention,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -40,74 +59,240 @@ pub struct Config {
 	project_root: PathBuf,
 	project_name: String,
 	project_targets: HashMap<Target, String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	log_retention: Option<usize>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	profiles: BTreeMap<String, Profile>,
+	/// The profile to use when none is selected by `--profile` or `UEBUILD_PROFILE`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	default_profile: Option<String>,
+	/// The name of the profile selected for this run.
+	#[serde(skip)]
+	active_profile: Option<String>,
+	/// Where the project config is read from and saved to.
+	#[serde(skip)]
+	cfg_path: PathBuf,
+	/// The layer that each value was taken from.
+	#[serde(skip)]
+	origins: HashMap<Key, Origin>,
+	/// The values to save in place of those overridden by layers which are not persisted (e.g. environment variables).
+	#[serde(skip)]
+	persisted_values: HashMap<Key, Option<String>>,
 	#[serde(skip)]
 	project: UProject,
 	#[serde(skip)]
+	plugins: Vec<DiscoveredPlugin>,
+	#[serde(skip)]
+	targets: Vec<TargetRules>,
+	#[serde(skip)]
+	engine_version: Option<EngineVersion>,
+	#[serde(skip)]
 	engine: Engine,
 	#[serde(skip)]
 	game: Game,
 }
 
+/// The number of run logs kept when [`Key::LogRetention`] is not set.
+const DEFAULT_LOG_RETENTION: usize = 20;
+
 impl Config {
-	fn instance() -> &'static mut Option<Self> {
-		static mut INSTANCE: Option<Config> = None;
-		unsafe { &mut INSTANCE }
+	fn exe_name_stem() -> String {
+		let mut path = std::env::current_exe().ok().unwrap();
+		path.set_extension("");
+		path.file_name().unwrap().to_str().unwrap().to_owned()
+	}
+
+	fn cfg_file_name() -> String {
+		format!("{}-cfg.json", Self::exe_name_stem())
+	}
+
+	/// Loads the config for the project found by [`locate_project`],
+	/// by applying each layer in order of increasing precedence:
+	/// 1. values generated from the project on disk (only if there is no project config file)
+	/// 2. the per-user config ([`user_config_path`])
+	/// 3. the project config file (`{exe}-cfg.json`)
+	/// 4. the selected [`Profile`], from `--profile`, `UEBUILD_PROFILE` or the project's default profile
+	/// 5. `UEBUILD_*` environment variables
+	/// 6. `--set key=value` overrides
+	///
+	/// A project config from an older schema is upgraded in place using the runner, keeping a backup of the original.
+	#[tracing::instrument(name = "config", skip_all)]
+	pub async fn load(
+		command_line: &CommandLineLayer,
+		runner: &SharedRunner,
+	) -> anyhow::Result<Self> {
+		let location = locate_project(command_line.project.as_deref(), &Self::cfg_file_name())?;
+		let cfg_path = location.root.join(Self::cfg_file_name());
+		tracing::debug!(
+			"Using project root {} (uproject {:?})",
+			location.root.display(),
+			location.uproject
+		);
+		let project_config = match cfg_path.exists() {
+			false => None,
+			true => Some(Self::read_project_config(&cfg_path, runner).await?),
+		};
+
+		let mut config = match &project_config {
+			Some(_) => Self::default(),
+			None => {
+				let mut config = Self::generate_from_disk(&location).await?;
+				config.apply_layer(Origin::Generated, config.values());
+				config
+			}
+		};
+		config.cfg_path = cfg_path.clone();
+		if let Some(path) = user_config_path().filter(|path| path.exists()) {
+			let values = layers::read_user_config(&path).await?;
+			config.apply_layer(Origin::User(path), values);
+		}
+		if let Some(project_config) = project_config {
+			config.apply_layer(Origin::Project(cfg_path), project_config.values());
+			config.profiles = project_config.profiles;
+			config.default_profile = project_config.default_profile;
+		}
+		let profile_name = match &command_line.profile {
+			Some(name) => Some(name.clone()),
+			None => match std::env::var(PROFILE_ENV_VAR) {
+				Ok(name) if !name.is_empty() => Some(name),
+				_ => config.default_profile.clone(),
+			},
+		};
+		if let Some(name) = profile_name {
+			let profile = config.find_profile(&name)?;
+			let values = profile.values.clone().into_iter().collect();
+			config.apply_layer(Origin::Profile(name.clone()), values);
+			config.active_profile = Some(name);
+		}
+		for (key, value, var) in layers::read_env_overrides().into_iter() {
+			config.apply_layer(Origin::Environment(var), vec![(key, value)]);
+		}
+		config.apply_layer(Origin::CommandLine, command_line.values.clone());
+
+		config.load_configs().await?;
+		Ok(config)
 	}
 
-	pub fn set_global(inst: Self) {
-		*Self::instance() = Some(inst);
+	async fn read_project_config(path: &Path, runner: &SharedRunner) -> anyhow::Result<Self> {
+		let raw_file = tokio::fs::read_to_string(path).await?;
+		let mut value = serde_json::from_str::<serde_json::Value>(&raw_file)?;
+		if let Some(version) = schema::migrate(&mut value, path)? {
+			let backup_path = schema::backup_path(path, version);
+			tracing::info!(
+				"Upgrading {} from config version {version} to {CONFIG_VERSION}, the original is saved as {}",
+				path.display(),
+				backup_path.display()
+			);
+			runner.copy_file(path.to_owned(), backup_path).await?;
+			let content = serde_json::to_string_pretty(&value)?;
+			runner
+				.write_file(path.to_owned(), content.into_bytes())
+				.await?;
+		}
+		let mut unknown_fields = Vec::new();
+		let config = serde_ignored::deserialize(&value, |field| {
+			unknown_fields.push(field.to_string());
+		})?;
+		for field in unknown_fields.into_iter() {
+			if field != schema::VERSION_FIELD {
+				tracing::warn!("Ignoring unknown field {field:?} in {}", path.display());
+			}
+		}
+		Ok(config)
 	}
 
-	pub fn get_global() -> &'static Self {
-		Self::instance().as_ref().unwrap()
+	/// Every key which has a non-empty value.
+	fn values(&self) -> Vec<(Key, String)> {
+		enumset::EnumSet::<Key>::all()
+			.iter()
+			.filter_map(|key| Some((key, self.get(&key).filter(|value| !value.is_empty())?)))
+			.collect()
 	}
 
-	pub fn take_global() -> Option<Self> {
-		Self::instance().take()
+	fn apply_layer(&mut self, origin: Origin, values: Vec<(Key, String)>) {
+		if !values.is_empty() {
+			tracing::debug!("Applying {} values from {origin}", values.len());
+		}
+		for (key, value) in values.into_iter() {
+			tracing::trace!("{} = {value:?} from {origin}", key.name());
+			match origin.is_persisted() {
+				true => {
+					self.persisted_values.remove(&key);
+				}
+				false => {
+					let persisted = self.get(&key);
+					self.persisted_values.entry(key).or_insert(persisted);
+				}
+			}
+			self.set_value(&key, value);
+			self.origins.insert(key, origin.clone());
+		}
 	}
-}
 
-impl Config {
-	fn exe_name_stem() -> String {
-		let mut path = std::env::current_exe().ok().unwrap();
-		path.set_extension("");
-		path.file_name().unwrap().to_str().unwrap().to_owned()
+	/// The layer that the key's value was taken from, if it has been set.
+	pub fn origin(&self, key: &Key) -> Option<&Origin> {
+		self.origins.get(key)
 	}
 
-	fn runtime_root() -> anyhow::Result<PathBuf> {
-		Ok(match std::env::var("PROJECT_ROOT") {
-			Ok(root) => PathBuf::from_str(&root)?,
-			_ => std::env::current_dir()?,
+	pub fn profiles(&self) -> &BTreeMap<String, Profile> {
+		&self.profiles
+	}
+
+	pub fn find_profile(&self, name: &str) -> Result<&Profile, UnknownProfile> {
+		self.profiles.get(name).ok_or_else(|| UnknownProfile {
+			name: name.to_owned(),
+			known: self.profiles.keys().cloned().collect(),
 		})
 	}
 
-	fn cfg_path() -> anyhow::Result<PathBuf> {
-		Ok(Self::runtime_root()?.join(format!("{}-cfg.json", Self::exe_name_stem())))
+	/// The name of the profile that was selected for this run, if any.
+	pub fn active_profile_name(&self) -> Option<&String> {
+		self.active_profile.as_ref()
 	}
 
-	pub async fn load() -> anyhow::Result<Self> {
-		let cfg_path = Self::cfg_path()?;
-		let mut config = match cfg_path.exists() {
-			false => Self::generate_from_disk().await?,
-			true => {
-				let raw_file = tokio::fs::read_to_string(&cfg_path).await?;
-				serde_json::from_str::<Self>(&raw_file)?
-			}
-		};
-		config.load_configs().await?;
-		Ok(config)
+	/// The profile that was selected for this run, if any.
+	pub fn active_profile(&self) -> Option<&Profile> {
+		self.profiles.get(self.active_profile.as_ref()?)
 	}
 
-	async fn generate_from_disk() -> anyhow::Result<Self> {
-		let cwd = Self::runtime_root()?;
+	/// The profile that is used when none is selected by `--profile` or `UEBUILD_PROFILE`.
+	pub fn default_profile(&self) -> Option<&String> {
+		self.default_profile.as_ref()
+	}
+
+	pub(crate) fn insert_profile(&mut self, name: String, profile: Profile) {
+		self.profiles.insert(name, profile);
+	}
+
+	/// Removes a profile, and clears the default profile if it was the one removed.
+	pub(crate) fn remove_profile(&mut self, name: &str) -> Result<Profile, UnknownProfile> {
+		self.find_profile(name)?;
+		if self.default_profile.as_deref() == Some(name) {
+			self.default_profile = None;
+		}
+		Ok(self.profiles.remove(name).unwrap())
+	}
+
+	pub(crate) fn set_default_profile(
+		&mut self,
+		name: Option<String>,
+	) -> Result<(), UnknownProfile> {
+		if let Some(name) = &name {
+			self.find_profile(name)?;
+		}
+		self.default_profile = name;
+		Ok(())
+	}
+
+	async fn generate_from_disk(location: &ProjectLocation) -> anyhow::Result<Self> {
 		let mut config = Self::default();
 
-		let uproject_path = match find_uproject(&cwd)? {
-			Some(path) => path,
+		let uproject_path = match &location.uproject {
+			Some(path) => path.clone(),
 			None => {
-				println!(
-					"Failed to find uproject file in {cwd}, using empty config.",
-					cwd = cwd.display()
+				tracing::warn!(
+					"Failed to find uproject file in {} or its parents, using empty config.",
+					location.root.display()
 				);
 				return Ok(Self::default());
 			}
@@ -123,16 +308,26 @@ impl Config {
 		config.project = unreal::UProject::read(&uproject_path)
 			.await
 			.context("read uproject")?;
-		let engine_path = find_engine_path(
-			&config.project_root,
+		let engine_path = EngineRegistry::discover().await.resolve(
 			config.project.get_engine_association(),
+			&config.project_root,
 		);
 
 		if let Some(path) = engine_path {
 			config.engine_path = path.clone();
 
+			let host = HostPlatform::current();
+			let binaries_dir = host.binaries_dir();
+			let editor_name = match EngineVersion::read(&path).await {
+				Ok(version) => version.editor_name(),
+				Err(_) => UE4_EDITOR_NAME,
+			};
+			let editor_name = format!("{editor_name}-{}-DebugGame", host.target_platform().as_ue());
 			let editor_target = EditorTarget::read(
-				&path.join(format!("Binaries/Win64/UE4Editor-Win64-DebugGame.target")),
+				&path
+					.join(&binaries_dir)
+					.join(&editor_name)
+					.with_extension("target"),
 			)
 			.await;
 			config.editor_binary_path = match editor_target {
@@ -143,38 +338,38 @@ impl Config {
 						_ => binary_path,
 					}
 				}
-				_ => PathBuf::from_str("Binaries/Win64/UE4Editor-Win64-DebugGame.exe").unwrap(),
+				_ => binaries_dir.join(host.engine_executable(&editor_name)),
 			};
 		}
 
-		config
-			.project_targets
-			.insert(Target::Client, config.project_name.clone());
-		if config
-			.project_root
-			.join(format!("Source/{}Editor.Target.cs", config.project_name))
-			.exists()
-		{
-			config
-				.project_targets
-				.insert(Target::Editor, format!("{}Editor", config.project_name));
-		}
-		if config
-			.project_root
-			.join(format!("Source/{}Server.Target.cs", config.project_name))
-			.exists()
-		{
-			config
-				.project_targets
-				.insert(Target::Server, format!("{}Server", config.project_name));
+		let targets = unreal::discover_targets(&config.project_root).await;
+		for shorthand in [Target::Editor, Target::Client, Target::Server] {
+			let name = default_target_name(&config.project_name, shorthand, &targets);
+			if let Some(name) = name {
+				config.project_targets.insert(shorthand, name);
+			}
 		}
 
 		Ok(config)
 	}
 
 	async fn load_configs(&mut self) -> anyhow::Result<()> {
-		self.engine = Engine::load(&self.project_root).await?;
-		self.game = Game::load(&self.project_root).await?;
+		self.engine_version = EngineVersion::read(&self.engine_path).await.ok();
+		// The uproject is not saved with the config, so it must be read when loading from disk.
+		let uproject_path = self.uproject_path();
+		if uproject_path.exists() {
+			self.project = UProject::read(&uproject_path)
+				.await
+				.context("read uproject")?;
+		}
+		self.plugins = unreal::discover_project_plugins(&self.project_root, &self.project).await;
+		self.targets = unreal::discover_targets(&self.project_root).await;
+		let platform = Some(HostPlatform::current().target_platform());
+		let engine_ini = self.load_ini("Engine", platform).await?;
+		self.engine = Engine::load(&engine_ini);
+		let game_ini = self.load_ini("Game", platform).await?;
+		let maps = unreal::discover_maps(&self.project_root, &self.plugins);
+		self.game = Game::load(&game_ini, maps);
 		Ok(())
 	}
 
@@ -187,10 +382,19 @@ impl Config {
 			Key::ProjectEditorTarget => self.project_targets.get(&Target::Editor).cloned(),
 			Key::ProjectClientTarget => self.project_targets.get(&Target::Client).cloned(),
 			Key::ProjectServerTarget => self.project_targets.get(&Target::Server).cloned(),
+			Key::LogRetention => self.log_retention.map(|count| count.to_string()),
 		}
 	}
 
+	/// Sets the value of a key, which will be saved with the project config.
 	pub(crate) fn set(&mut self, key: &Key, value: String) {
+		self.persisted_values.remove(key);
+		self.origins
+			.insert(*key, Origin::Project(self.cfg_path.clone()));
+		self.set_value(key, value);
+	}
+
+	fn set_value(&mut self, key: &Key, value: String) {
 		match key {
 			Key::EnginePath => {
 				self.engine_path = PathBuf::from_str(&value).unwrap();
@@ -213,14 +417,47 @@ impl Config {
 			Key::ProjectServerTarget => {
 				self.project_targets.insert(Target::Server, value);
 			}
+			Key::LogRetention => match value.parse() {
+				Ok(count) => self.log_retention = Some(count),
+				Err(_) => {
+					tracing::warn!(
+						"Ignoring {value:?} for log-retention, expected a number of logs"
+					)
+				}
+			},
 		}
 	}
 
-	pub(crate) async fn save(&self) -> anyhow::Result<()> {
-		let cfg_path = Self::cfg_path()?;
-		let content = serde_json::to_string_pretty(&self)?;
-		println!("Saving current configuration to {:?}", cfg_path);
-		tokio::fs::write(&cfg_path, content).await?;
+	pub(crate) async fn save(&self, runner: &SharedRunner) -> anyhow::Result<()> {
+		let cfg_path = self.cfg_path.clone();
+		// Values from the per-user config, environment and command line are not saved with the project
+		let mut persisted = self.clone();
+		for (key, value) in self.persisted_values.iter() {
+			match (key, value) {
+				(_, Some(value)) => persisted.set_value(key, value.clone()),
+				(Key::ProjectEditorTarget, None) => {
+					persisted.project_targets.remove(&Target::Editor);
+				}
+				(Key::ProjectClientTarget, None) => {
+					persisted.project_targets.remove(&Target::Client);
+				}
+				(Key::ProjectServerTarget, None) => {
+					persisted.project_targets.remove(&Target::Server);
+				}
+				(Key::LogRetention, None) => {
+					persisted.log_retention = None;
+				}
+				(_, None) => {}
+			}
+		}
+		let mut fields = serde_json::Map::new();
+		fields.insert(schema::VERSION_FIELD.to_owned(), CONFIG_VERSION.into());
+		if let serde_json::Value::Object(values) = serde_json::to_value(&persisted)? {
+			fields.extend(values);
+		}
+		let content = serde_json::to_string_pretty(&fields)?;
+		tracing::info!("Saving current configuration to {:?}", cfg_path);
+		runner.write_file(cfg_path, content.into_bytes()).await?;
 		Ok(())
 	}
 
@@ -228,6 +465,19 @@ impl Config {
 		&self.engine_path
 	}
 
+	/// The version of the engine at [`engine_path`](Self::engine_path), if it could be read.
+	pub fn engine_version(&self) -> Option<&EngineVersion> {
+		self.engine_version.as_ref()
+	}
+
+	/// The base name of the editor's binaries, targets and modules files.
+	/// Assumes Unreal Engine 4 naming when the engine version is unknown.
+	pub fn editor_name(&self) -> &'static str {
+		self.engine_version()
+			.map(EngineVersion::editor_name)
+			.unwrap_or(UE4_EDITOR_NAME)
+	}
+
 	pub fn editor_binary(&self) -> PathBuf {
 		self.engine_path().join(&self.editor_binary_path)
 	}
@@ -240,6 +490,11 @@ impl Config {
 		&self.project_name
 	}
 
+	/// If the config refers to a project, which is not the case when no `.uproject` could be found.
+	pub fn has_project(&self) -> bool {
+		!self.project_name.is_empty()
+	}
+
 	pub fn uproject_path(&self) -> PathBuf {
 		self.project_root()
 			.join(&self.project_name)
@@ -250,10 +505,86 @@ impl Config {
 		&self.project
 	}
 
+	/// The plugins found in the project's `Plugins` directory and its `AdditionalPluginDirectories`.
+	pub fn plugins(&self) -> &Vec<DiscoveredPlugin> {
+		&self.plugins
+	}
+
 	pub fn get_project_target(&self, target: Target) -> Option<&String> {
 		self.project_targets.get(&target)
 	}
 
+	/// The targets declared by the project's `Source/*.Target.cs` files, sorted by name.
+	pub fn targets(&self) -> &Vec<TargetRules> {
+		&self.targets
+	}
+
+	/// Resolves the selector to the name of a target to pass to the build tool.
+	/// Target names are matched case-insensitively against the discovered targets.
+	pub fn resolve_target(&self, selector: &TargetSelector) -> Result<String, UnknownTarget> {
+		let name = match selector {
+			TargetSelector::Shorthand(target) => match self.get_project_target(*target) {
+				Some(name) => return Ok(name.clone()),
+				None => target.as_ue(),
+			},
+			TargetSelector::Name(name) => name.as_str(),
+		};
+		match self
+			.targets
+			.iter()
+			.find(|target| target.name.eq_ignore_ascii_case(name))
+		{
+			Some(target) => Ok(target.name.clone()),
+			None => Err(UnknownTarget {
+				name: name.to_owned(),
+				available: self
+					.targets
+					.iter()
+					.map(|target| target.name.clone())
+					.collect(),
+			}),
+		}
+	}
+
+	/// The directory that uebuild writes a log of each run to.
+	pub fn run_logs_dir(&self) -> PathBuf {
+		self.project_root.join("Saved/Logs/uebuild")
+	}
+
+	/// The number of run logs to keep, where `0` keeps every log.
+	pub fn log_retention(&self) -> usize {
+		self.log_retention.unwrap_or(DEFAULT_LOG_RETENTION)
+	}
+
+	/// The directory that the editor on this host saves user config overrides to.
+	pub fn saved_config_dir(&self) -> PathBuf {
+		let platform = HostPlatform::current().target_platform().ini_name();
+		let dir_name = match self.editor_name() {
+			UE4_EDITOR_NAME => platform.to_owned(),
+			_ => format!("{platform}Editor"),
+		};
+		self.project_root.join("Saved/Config").join(dir_name)
+	}
+
+	pub fn ini_locations(&self) -> IniLocations {
+		IniLocations {
+			engine_dir: self.engine_path.clone(),
+			project_root: self.project_root.clone(),
+			saved_config_dir: Some(self.saved_config_dir()),
+		}
+	}
+
+	/// Reads and merges every layer of the config category (e.g. `Engine` or `Game`) for the platform.
+	pub async fn load_ini(
+		&self,
+		category: &str,
+		platform: Option<Platform>,
+	) -> anyhow::Result<IniHierarchy> {
+		IniHierarchy::load(&self.ini_locations(), category, platform)
+			.await
+			.context(format!("load {category} config"))
+	}
+
 	pub fn engine(&self) -> &Engine {
 		&self.engine
 	}
@@ -263,61 +594,111 @@ impl Config {
 	}
 }
 
-#[derive(thiserror::Error, Debug)]
-pub struct MissingValue(Key);
-impl std::fmt::Display for MissingValue {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "Missing config value for key {:?}", self.0)
+#[cfg(test)]
+impl Config {
+	/// A config for the project at `root` with conventionally named targets, without reading anything from disk.
+	pub(crate) fn for_project(
+		engine_path: &Path,
+		root: &Path,
+		name: &str,
+		project: UProject,
+	) -> Self {
+		let mut config = Self {
+			engine_path: engine_path.to_owned(),
+			editor_binary_path: PathBuf::from("Binaries/Linux/UE4Editor"),
+			project_root: root.to_owned(),
+			project_name: name.to_owned(),
+			project,
+			..Self::default()
+		};
+		config
+			.project_targets
+			.insert(Target::Editor, format!("{name}Editor"));
+		config
+			.project_targets
+			.insert(Target::Client, name.to_owned());
+		config
+			.project_targets
+			.insert(Target::Server, format!("{name}Server"));
+		config
 	}
 }
 
-fn find_uproject(cwd: &Path) -> anyhow::Result<Option<PathBuf>> {
-	if let Some(path) = glob_path_exists(&cwd, "/*.uproject")? {
-		return Ok(Some(path));
-	}
-	if let Some(path) = glob_path_exists(&cwd, "/Game/*.uproject")? {
-		return Ok(Some(path));
-	}
-	Ok(None)
+/// Picks the target which a shorthand refers to by default.
+/// When several targets have the matching type, the one following the `{Project}{Suffix}` convention is preferred.
+/// The client shorthand falls back to the game target, which can also run as a client, when there is no client target.
+fn default_target_name(
+	project_name: &str,
+	shorthand: Target,
+	targets: &[TargetRules],
+) -> Option<String> {
+	let preferences = match shorthand {
+		Target::Editor => vec![(TargetType::Editor, format!("{project_name}Editor"))],
+		Target::Client => vec![
+			(TargetType::Client, format!("{project_name}Client")),
+			(TargetType::Game, project_name.to_owned()),
+		],
+		Target::Server => vec![(TargetType::Server, format!("{project_name}Server"))],
+	};
+	if targets.is_empty() {
+		// Nothing to inspect (e.g. a content-only project), so assume the conventional game target exists
+		return (shorthand == Target::Client).then(|| project_name.to_owned());
+	}
+	preferences
+		.into_iter()
+		.find_map(|(target_type, conventional_name)| {
+			let mut of_type = targets
+				.iter()
+				.filter(|target| target.target_type == target_type);
+			let target = of_type
+				.clone()
+				.find(|target| target.name == conventional_name)
+				.or_else(|| of_type.next())?;
+			Some(target.name.clone())
+		})
 }
 
-fn glob_path_exists(root: &Path, glob_fmt: &str) -> Result<Option<PathBuf>, glob::PatternError> {
-	for entry in glob::glob(&format!("{dir}{glob_fmt}", dir = root.display()))? {
-		if let Ok(path) = entry {
-			return Ok(Some(path));
+#[derive(thiserror::Error, Debug)]
+pub struct UnknownTarget {
+	pub name: String,
+	pub available: Vec<String>,
+}
+impl std::fmt::Display for UnknownTarget {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "No target named {:?} in Source/*.Target.cs", self.name)?;
+		match self.available.is_empty() {
+			true => write!(f, " (the project has no targets)"),
+			false => write!(f, ", expected one of: {}", self.available.join(", ")),
 		}
 	}
-	Ok(None)
 }
 
-fn find_engine_path(project_root: &Path, engine_association: Option<&String>) -> Option<PathBuf> {
-	match engine_association {
-		Some(version) => {
-			// Using a pre-installed engine. Check program files for installment.
-			let path = PathBuf::from(format!(
-				"C:\\Program Files\\Epic Games\\UE_{version}\\Engine"
-			));
-			if path.exists() {
-				Some(path)
-			} else {
-				println!(
-					"Failed to find pre-installed engine for v{version} at {}",
-					path.display()
-				);
+/// Parses each struct or array value, printing and skipping those which are malformed.
+fn parse_ini_values(values: Vec<&str>) -> impl Iterator<Item = (IniValue, &str)> {
+	values
+		.into_iter()
+		.filter_map(|text| match IniValue::parse(text) {
+			Ok(value) => Some((value, text)),
+			Err(err) => {
+				tracing::warn!("Ignoring malformed ini value {text:?}: {err}");
 				None
 			}
-		}
-		None => {
-			// Must be using a custom engine. We should check the parent directory for an "Engine" dir and contents.
-			let mut path = project_root.parent().unwrap().to_owned();
-			path.push("Engine");
-			if path.exists() {
-				Some(path)
-			} else {
-				println!("Failed to find custom engine at {}", path.display());
-				None
-			}
-		}
+		})
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct LoadConfigError;
+impl std::fmt::Display for LoadConfigError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Failed to load configuration")
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct MissingValue(Key);
+impl std::fmt::Display for MissingValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Missing config value for key {:?}", self.0)
 	}
 }
 
@@ -325,27 +706,47 @@ fn find_engine_path(project_root: &Path, engine_association: Option<&String>) ->
 pub struct Engine {
 	default_map_server: Option<String>,
 	default_map_game: Option<String>,
+	game_mode_aliases: Vec<GameModeAlias>,
 	mode_aliases: Vec<String>,
 }
 
-impl Engine {
-	async fn load(project_root: &Path) -> anyhow::Result<Self> {
-		let path = project_root.join("Config/DefaultEngine.ini");
-		let text = tokio::fs::read_to_string(&path)
-			.await
-			.context("read DefaultEngine.ini")?;
-		let content = ini::Ini::load_from_str(&text)?;
+/// An entry of `GameModeClassAliases`, which allows a short name to be used in place of a game mode's class path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameModeAlias {
+	pub name: String,
+	/// The path of the game mode class (e.g. `/Game/Modes/BP_DM.BP_DM_C`).
+	pub game_mode: String,
+}
 
+impl GameModeAlias {
+	/// Reads a `(Name="...",GameMode="...")` struct.
+	pub fn from_value(value: &IniValue) -> Option<Self> {
+		Some(Self {
+			name: value.field_str("Name")?.to_owned(),
+			game_mode: value.field_str("GameMode")?.to_owned(),
+		})
+	}
+}
+
+impl Engine {
+	fn load(ini: &IniHierarchy) -> Self {
 		let mut engine = Self::default();
-		if let Some(map_settings) = content.section(Some("/Script/EngineSettings.GameMapsSettings"))
-		{
+		if let Some(map_settings) = ini.section("/Script/EngineSettings.GameMapsSettings") {
 			engine.default_map_server = map_settings.get("ServerDefaultMap").map(str::to_owned);
 			engine.default_map_game = map_settings.get("GameDefaultMap").map(str::to_owned);
 
+			for (value, text) in parse_ini_values(map_settings.get_all("GameModeClassAliases")) {
+				match GameModeAlias::from_value(&value) {
+					Some(alias) => engine.game_mode_aliases.push(alias),
+					None => tracing::warn!(
+						"Ignoring GameModeClassAliases entry without a Name and GameMode: {text}"
+					),
+				}
+			}
+
 			let mut all_aliases = HashMap::new();
-			for value in map_settings.get_all("+GameModeClassAliases") {
-				let value = value.strip_prefix("(Name=\"").unwrap_or(value);
-				let value = value.split("\"").next().unwrap();
+			for alias in engine.game_mode_aliases.iter() {
+				let value = alias.name.as_str();
 				all_aliases.insert(
 					value.to_owned(),
 					match value == value.to_lowercase() {
@@ -366,8 +767,7 @@ impl Engine {
 			engine.mode_aliases = aliases.into_iter().collect();
 			engine.mode_aliases.sort();
 		}
-
-		Ok(engine)
+		engine
 	}
 
 	pub fn default_map_server(&self) -> Option<&String> {
@@ -383,62 +783,152 @@ impl Engine {
 			.default_map_server
 			.as_ref()
 			.or(self.default_map_game.as_ref());
-		let Some(map) = map else { return None; };
-		let Ok(mut path) = PathBuf::from_str(map) else { return None; };
+		let mut path = PathBuf::from(map?);
 		path.set_extension("");
 		Some(path)
 	}
 
+	/// The names of the game mode aliases, excluding aliases which only differ from another by case.
 	pub fn mode_aliases(&self) -> &Vec<String> {
 		&self.mode_aliases
 	}
+
+	pub fn game_mode_aliases(&self) -> &Vec<GameModeAlias> {
+		&self.game_mode_aliases
+	}
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Game {
-	maps_to_cook: Vec<PathBuf>,
+	maps: Vec<MapAsset>,
 }
 
 impl Game {
-	async fn load(project_root: &Path) -> anyhow::Result<Self> {
-		let path = project_root.join("Config/DefaultGame.ini");
-		let text = tokio::fs::read_to_string(&path)
-			.await
-			.context("read DefaultGame.ini")?;
-		let content = ini::Ini::load_from_str(&text)?;
-
+	fn load(ini: &IniHierarchy, mut maps: Vec<MapAsset>) -> Self {
 		let mut maps_to_cook = Vec::new();
-		if let Some(packaging) = content.section(Some("/Script/UnrealEd.ProjectPackagingSettings"))
-		{
-			for value in packaging.get_all("+MapsToCook") {
-				let value = value.strip_prefix("(FilePath=\"").unwrap_or(value);
-				let value = value.strip_suffix("\")").unwrap_or(value);
-				if let Ok(path) = PathBuf::from_str(value) {
-					maps_to_cook.push(path);
+		if let Some(packaging) = ini.section("/Script/UnrealEd.ProjectPackagingSettings") {
+			for (value, text) in parse_ini_values(packaging.get_all("MapsToCook")) {
+				match value.field_str("FilePath") {
+					Some(path) => maps_to_cook.push(PathBuf::from(path)),
+					None => tracing::warn!("Ignoring MapsToCook entry without a FilePath: {text}"),
 				}
 			}
 		}
 
-		Ok(Self { maps_to_cook })
+		for path in maps_to_cook.iter() {
+			// Entries may be written as either a package name or an object path (`/Game/Maps/Arena.Arena`)
+			let package = path.with_extension("");
+			let package = package.to_string_lossy();
+			match maps
+				.iter_mut()
+				.find(|map| map.package.eq_ignore_ascii_case(&package))
+			{
+				Some(map) => map.cooked = true,
+				None => maps.push(MapAsset {
+					package: package.into_owned(),
+					file: None,
+					cooked: true,
+				}),
+			}
+		}
+
+		Self { maps }
 	}
 
-	pub fn maps_by_name(&self) -> HashMap<String, &PathBuf> {
-		self.maps_to_cook
+	/// Every map in the project and its plugins, as well as any maps to cook which were not found on disk.
+	pub fn maps(&self) -> &Vec<MapAsset> {
+		&self.maps
+	}
+
+	/// The maps keyed by their short name.
+	/// Maps whose names are shared with another map are keyed by their package name instead.
+	pub fn maps_by_name(&self) -> BTreeMap<String, &MapAsset> {
+		self.maps
 			.iter()
-			.map(PathBuf::as_path)
-			.filter_map(Path::file_name)
-			.filter_map(std::ffi::OsStr::to_str)
-			.map(str::to_owned)
-			.zip(self.maps_to_cook.iter())
+			.map(|map| {
+				let is_unique = self
+					.maps
+					.iter()
+					.filter(|other| other.name().eq_ignore_ascii_case(map.name()))
+					.count() == 1;
+				let key = match is_unique {
+					true => map.name().to_owned(),
+					false => map.package.clone(),
+				};
+				(key, map)
+			})
 			.collect()
 	}
+
+	/// Finds a map by its short name (if unique) or package name, ignoring case.
+	pub fn find_map(&self, name: &str) -> Option<&MapAsset> {
+		self.maps_by_name()
+			.into_iter()
+			.find(|(key, map)| {
+				key.eq_ignore_ascii_case(name) || map.package.eq_ignore_ascii_case(name)
+			})
+			.map(|(_, map)| map)
+	}
 }
 
-pub(crate) fn read_ini_prop<'a>(ini_content: &'a str, prop_name: &str) -> Option<&'a str> {
-	let game_default_map = regex::Regex::new(&format!("{prop_name}=(?P<value>.*)")).unwrap();
-	let captures = match game_default_map.captures(&ini_content) {
-		Some(matches) => matches,
-		None => return None,
-	};
-	captures.name("value").map(|v| v.as_str().trim())
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn targets(targets: &[(&str, TargetType)]) -> Vec<TargetRules> {
+		targets
+			.iter()
+			.map(|(name, target_type)| TargetRules {
+				name: name.to_string(),
+				target_type: *target_type,
+				path: PathBuf::from(format!("Source/{name}.Target.cs")),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn client_prefers_a_client_target() {
+		let targets = targets(&[
+			("MyGame", TargetType::Game),
+			("MyGameClient", TargetType::Client),
+			("MyGameEditor", TargetType::Editor),
+		]);
+		let name = default_target_name("MyGame", Target::Client, &targets);
+		assert_eq!(name.as_deref(), Some("MyGameClient"));
+	}
+
+	#[test]
+	fn client_falls_back_to_the_game_target() {
+		let targets = targets(&[
+			("MyGameEditor", TargetType::Editor),
+			("Other", TargetType::Game),
+			("MyGame", TargetType::Game),
+		]);
+		let name = default_target_name("MyGame", Target::Client, &targets);
+		assert_eq!(name.as_deref(), Some("MyGame"));
+		let name = default_target_name("MyGame", Target::Server, &targets);
+		assert_eq!(name, None);
+	}
+
+	#[test]
+	fn unconventional_names_are_found_by_type() {
+		let targets = targets(&[
+			("Tool", TargetType::Program),
+			("Shooter", TargetType::Client),
+			("ShooterEd", TargetType::Editor),
+		]);
+		let name = default_target_name("MyGame", Target::Client, &targets);
+		assert_eq!(name.as_deref(), Some("Shooter"));
+		let name = default_target_name("MyGame", Target::Editor, &targets);
+		assert_eq!(name.as_deref(), Some("ShooterEd"));
+	}
+
+	#[test]
+	fn content_only_projects_assume_a_game_target() {
+		assert_eq!(
+			default_target_name("MyGame", Target::Client, &[]).as_deref(),
+			Some("MyGame")
+		);
+		assert_eq!(default_target_name("MyGame", Target::Editor, &[]), None);
+	}
 }
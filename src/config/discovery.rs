@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+/// Where a project was found: the directory holding (or that will hold) the project config,
+/// and the `.uproject` if one was found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectLocation {
+	pub root: PathBuf,
+	pub uproject: Option<PathBuf>,
+}
+
+/// Finds the project to operate on.
+///
+/// - `--project <path>` (a `.uproject`, or a directory containing one or a project config) is used as is.
+/// - `PROJECT_ROOT` is searched without looking at its parents.
+/// - Otherwise the current directory and each of its parents are searched,
+///   stopping at the nearest directory containing a project config (`cfg_name`) or `.uproject`.
+///
+/// A directory is searched for `*.uproject`, then `Game/*.uproject`.
+/// If neither the current directory nor its parents contain a project, the current directory is used without a project.
+/// Fails with [`AmbiguousProject`] if the nearest directory with a `.uproject` contains several of them.
+pub fn locate_project(explicit: Option<&Path>, cfg_name: &str) -> anyhow::Result<ProjectLocation> {
+	if let Some(path) = explicit {
+		if !path.exists() {
+			return Err(ProjectNotFound(path.to_owned()).into());
+		}
+		let path = std::path::absolute(path)?;
+		if path.is_file() {
+			return Ok(ProjectLocation {
+				root: path.parent().unwrap().to_owned(),
+				uproject: Some(path),
+			});
+		}
+		return match search_dir(&path, cfg_name)? {
+			Some(location) => Ok(location),
+			None => Err(ProjectNotFound(path))?,
+		};
+	}
+
+	if let Ok(root) = std::env::var("PROJECT_ROOT") {
+		let root = PathBuf::from(root);
+		return Ok(match search_dir(&root, cfg_name)? {
+			Some(location) => location,
+			None => ProjectLocation {
+				root,
+				uproject: None,
+			},
+		});
+	}
+
+	Ok(search_ancestors(&std::env::current_dir()?, cfg_name)?)
+}
+
+fn search_ancestors(cwd: &Path, cfg_name: &str) -> Result<ProjectLocation, AmbiguousProject> {
+	for dir in cwd.ancestors() {
+		if let Some(location) = search_dir(dir, cfg_name)? {
+			return Ok(location);
+		}
+	}
+	Ok(ProjectLocation {
+		root: cwd.to_owned(),
+		uproject: None,
+	})
+}
+
+fn search_dir(dir: &Path, cfg_name: &str) -> Result<Option<ProjectLocation>, AmbiguousProject> {
+	if dir.join(cfg_name).is_file() {
+		return Ok(Some(ProjectLocation {
+			root: dir.to_owned(),
+			uproject: None,
+		}));
+	}
+	for pattern in ["*.uproject", "Game/*.uproject"] {
+		let pattern = format!(
+			"{}/{pattern}",
+			glob::Pattern::escape(&dir.to_string_lossy())
+		);
+		let mut uprojects = match glob::glob(&pattern) {
+			Ok(paths) => paths.flatten().collect::<Vec<_>>(),
+			Err(_) => continue,
+		};
+		match uprojects.len() {
+			0 => continue,
+			1 => {
+				return Ok(Some(ProjectLocation {
+					root: dir.to_owned(),
+					uproject: uprojects.pop(),
+				}))
+			}
+			_ => {
+				uprojects.sort();
+				return Err(AmbiguousProject(uprojects));
+			}
+		}
+	}
+	Ok(None)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct AmbiguousProject(pub Vec<PathBuf>);
+impl std::fmt::Display for AmbiguousProject {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"Found several projects, choose one with --project <path>:"
+		)?;
+		for path in self.0.iter() {
+			write!(f, "\n  {}", path.display())?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct ProjectNotFound(pub PathBuf);
+impl std::fmt::Display for ProjectNotFound {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"No .uproject or project config found at {}",
+			self.0.display()
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::temp_dir;
+
+	#[test]
+	fn finds_uproject_in_ancestor() {
+		let root = temp_dir("discovery-ancestor");
+		std::fs::write(root.join("Game.uproject"), "{}").unwrap();
+		let cwd = root.join("Source/Game");
+		std::fs::create_dir_all(&cwd).unwrap();
+		let location = search_ancestors(&cwd, "uebuild-cfg.json").unwrap();
+		assert_eq!(location.root, root);
+		assert_eq!(location.uproject, Some(root.join("Game.uproject")));
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn ambiguous_ancestor_is_an_error() {
+		let root = temp_dir("discovery-ambiguous");
+		std::fs::write(root.join("B.uproject"), "{}").unwrap();
+		std::fs::write(root.join("A.uproject"), "{}").unwrap();
+		let cwd = root.join("empty");
+		std::fs::create_dir_all(&cwd).unwrap();
+		let err = search_ancestors(&cwd, "uebuild-cfg.json").unwrap_err();
+		assert_eq!(err.0, [root.join("A.uproject"), root.join("B.uproject")]);
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+}
@@ -0,0 +1,143 @@
+use super::Key;
+use crate::utility::home_dir;
+use anyhow::Context;
+use clap::ValueEnum;
+use std::path::{Path, PathBuf};
+
+/// The prefix of environment variables which override config values (e.g. `UEBUILD_ENGINE_PATH`).
+pub const ENV_PREFIX: &str = "UEBUILD_";
+/// The environment variable which selects a profile, if `--profile` is not provided.
+pub const PROFILE_ENV_VAR: &str = "UEBUILD_PROFILE";
+
+/// The layer of configuration that a value was taken from.
+///
+/// Layers are applied in the order they are declared here, so values from later layers take precedence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Origin {
+	/// Discovered from the project and engines on disk, when there is no project config file.
+	Generated,
+	/// The per-user `config.toml`.
+	User(PathBuf),
+	/// The project's `{exe}-cfg.json`.
+	Project(PathBuf),
+	/// The selected profile in the project config.
+	Profile(String),
+	/// A `UEBUILD_*` environment variable.
+	Environment(String),
+	/// A `--set key=value` flag.
+	CommandLine,
+}
+
+impl Origin {
+	/// If values from this layer are written back when the project config is saved.
+	/// Per-user and per-invocation overrides are never saved into the project config.
+	pub fn is_persisted(&self) -> bool {
+		matches!(self, Self::Generated | Self::Project(_))
+	}
+}
+
+impl std::fmt::Display for Origin {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Generated => write!(f, "generated"),
+			Self::User(path) => write!(f, "user config {}", path.display()),
+			Self::Project(path) => write!(f, "project config {}", path.display()),
+			Self::Profile(name) => write!(f, "profile {name:?}"),
+			Self::Environment(var) => write!(f, "environment variable {var}"),
+			Self::CommandLine => write!(f, "command line --set"),
+		}
+	}
+}
+
+impl Key {
+	/// The name of the key as used by `cfg`, `--set` and the user config (e.g. `engine-path`).
+	pub fn name(&self) -> String {
+		self.to_possible_value().unwrap().get_name().to_owned()
+	}
+
+	/// The environment variable which overrides the key (e.g. `UEBUILD_ENGINE_PATH`).
+	pub fn env_var(&self) -> String {
+		format!(
+			"{ENV_PREFIX}{}",
+			self.name().replace('-', "_").to_uppercase()
+		)
+	}
+}
+
+/// The location of the per-user config: `$XDG_CONFIG_HOME/uebuild/config.toml`, or `~/.config/uebuild/config.toml`.
+pub fn user_config_path() -> Option<PathBuf> {
+	let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+		Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+		_ => home_dir()?.join(".config"),
+	};
+	Some(config_dir.join("uebuild/config.toml"))
+}
+
+/// Reads the top-level `key = "value"` pairs of the per-user config.
+/// Unknown keys and values which are not strings are reported and skipped.
+pub(super) async fn read_user_config(path: &Path) -> anyhow::Result<Vec<(Key, String)>> {
+	let text = tokio::fs::read_to_string(path)
+		.await
+		.context(format!("read {path:?}"))?;
+	let table = text
+		.parse::<toml::Table>()
+		.context(format!("parse {path:?}"))?;
+	let mut values = Vec::new();
+	for (name, value) in table.into_iter() {
+		let Ok(key) = Key::from_str(&name, true) else {
+			tracing::warn!("Ignoring unknown key {name:?} in {}", path.display());
+			continue;
+		};
+		match value {
+			toml::Value::String(value) => values.push((key, value)),
+			_ => tracing::warn!(
+				"Ignoring {name:?} in {}, its value must be a string",
+				path.display()
+			),
+		}
+	}
+	Ok(values)
+}
+
+/// The values of every `UEBUILD_*` variable which is set, paired with the variable's name.
+pub(super) fn read_env_overrides() -> Vec<(Key, String, String)> {
+	enumset::EnumSet::<Key>::all()
+		.iter()
+		.filter_map(|key| {
+			let var = key.env_var();
+			let value = std::env::var(&var).ok()?;
+			Some((key, value, var))
+		})
+		.collect()
+}
+
+/// Parses a `key=value` override, as passed to `--set`.
+pub fn parse_override(arg: &str) -> Result<(Key, String), String> {
+	let Some((name, value)) = arg.split_once('=') else {
+		return Err(format!("expected KEY=VALUE, found {arg:?}"));
+	};
+	let key = Key::from_str(name.trim(), true).map_err(|_| {
+		let names = Key::value_variants()
+			.iter()
+			.map(Key::name)
+			.collect::<Vec<_>>();
+		format!(
+			"unknown key {name:?}, expected one of: {}",
+			names.join(", ")
+		)
+	})?;
+	Ok((key, value.to_owned()))
+}
+
+/// The config options passed on the command line.
+#[derive(Clone, Debug, Default)]
+pub struct CommandLineLayer {
+	/// If `--dry-run` was provided, so that loading the config does not change files.
+	pub dry_run: bool,
+	/// The project selected by `--project`.
+	pub project: Option<PathBuf>,
+	/// The profile selected by `--profile`.
+	pub profile: Option<String>,
+	/// The `--set key=value` overrides.
+	pub values: Vec<(Key, String)>,
+}
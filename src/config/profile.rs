@@ -0,0 +1,83 @@
+use super::Key;
+use crate::types::{Configuration, Platform, Target, TargetSelector};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A named preset of config values and command defaults, stored in the project config.
+///
+/// Selected with `--profile <name>`, the `UEBUILD_PROFILE` environment variable, or `cfg profile use <name>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+	/// Values which override those in the project config.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub values: BTreeMap<Key, String>,
+	/// Defaults for `compile` options which are not provided on the command line.
+	#[serde(default, skip_serializing_if = "BuildDefaults::is_empty")]
+	pub compile: BuildDefaults<TargetSelector>,
+	/// Defaults for `cook` options which are not provided on the command line.
+	#[serde(default, skip_serializing_if = "BuildDefaults::is_empty")]
+	pub cook: BuildDefaults<Target>,
+}
+
+/// The options shared by commands which build the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildDefaults<T> {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub target: Option<T>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub platform: Option<Platform>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub configuration: Option<Configuration>,
+}
+
+impl<T> Default for BuildDefaults<T> {
+	fn default() -> Self {
+		Self {
+			target: None,
+			platform: None,
+			configuration: None,
+		}
+	}
+}
+
+impl<T> BuildDefaults<T> {
+	pub fn is_empty(&self) -> bool {
+		self.target.is_none() && self.platform.is_none() && self.configuration.is_none()
+	}
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for BuildDefaults<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut options = Vec::new();
+		if let Some(target) = &self.target {
+			options.push(format!("--target {target}"));
+		}
+		if let Some(platform) = &self.platform {
+			options.push(format!("--platform {}", value_name(platform)));
+		}
+		if let Some(configuration) = &self.configuration {
+			options.push(format!("--configuration {}", value_name(configuration)));
+		}
+		write!(f, "{}", options.join(" "))
+	}
+}
+
+fn value_name(value: &impl ValueEnum) -> String {
+	value.to_possible_value().unwrap().get_name().to_owned()
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct UnknownProfile {
+	pub name: String,
+	pub known: Vec<String>,
+}
+impl std::fmt::Display for UnknownProfile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "No profile named {:?}", self.name)?;
+		match self.known.is_empty() {
+			true => write!(f, ", create one with `cfg profile create`"),
+			false => write!(f, ", expected one of: {}", self.known.join(", ")),
+		}
+	}
+}
@@ -0,0 +1,133 @@
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// The version of the project config schema that this build reads and writes.
+///
+/// - 1: the unversioned serde form of [`Config`](super::Config).
+/// - 2: adds `version`, and `editor_binary_path` is always relative to `engine_path`.
+pub const CONFIG_VERSION: u64 = 2;
+
+/// The name of the field holding the schema version.
+pub(super) const VERSION_FIELD: &str = "version";
+
+/// Upgrades a config from the version before it, indexed by that version minus one.
+const MIGRATIONS: [fn(&mut Map<String, Value>); (CONFIG_VERSION - 1) as usize] = [migrate_v1];
+
+/// The schema version of a project config, where files without a version are version 1.
+pub(super) fn version_of(value: &Value) -> u64 {
+	value
+		.get(VERSION_FIELD)
+		.and_then(Value::as_u64)
+		.unwrap_or(1)
+}
+
+/// Upgrades a project config to the [current version](CONFIG_VERSION),
+/// returning the version it was upgraded from if it was changed.
+pub(super) fn migrate(
+	value: &mut Value,
+	path: &Path,
+) -> Result<Option<u64>, UnsupportedConfigVersion> {
+	let version = version_of(value);
+	// Versions start at 1, so 0 can only come from a hand-edited file
+	if version == 0 || version > CONFIG_VERSION {
+		return Err(UnsupportedConfigVersion {
+			path: path.to_owned(),
+			version,
+		});
+	}
+	if version == CONFIG_VERSION {
+		return Ok(None);
+	}
+	let Some(fields) = value.as_object_mut() else {
+		return Ok(None);
+	};
+	for migration in MIGRATIONS[(version - 1) as usize..].iter() {
+		migration(fields);
+	}
+	let mut upgraded = Map::new();
+	upgraded.insert(VERSION_FIELD.to_owned(), CONFIG_VERSION.into());
+	upgraded.extend(std::mem::take(fields));
+	*fields = upgraded;
+	Ok(Some(version))
+}
+
+/// Where the original copy of a config is kept when it is migrated (e.g. `uebuild-cfg.json.v1.bak`).
+pub(super) fn backup_path(path: &Path, version: u64) -> PathBuf {
+	let mut file_name = path.file_name().unwrap_or_default().to_owned();
+	file_name.push(format!(".v{version}.bak"));
+	path.with_file_name(file_name)
+}
+
+/// Version 1 allowed `editor_binary_path` to be absolute when set through `cfg`,
+/// even though it is joined to the engine path when used.
+fn migrate_v1(fields: &mut Map<String, Value>) {
+	let engine_path = fields
+		.get("engine_path")
+		.and_then(Value::as_str)
+		.map(PathBuf::from);
+	let editor_binary_path = fields
+		.get("editor_binary_path")
+		.and_then(Value::as_str)
+		.map(PathBuf::from);
+	if let (Some(engine_path), Some(editor_binary_path)) = (engine_path, editor_binary_path) {
+		if let Ok(relative) = editor_binary_path.strip_prefix(&engine_path) {
+			let relative = relative.to_string_lossy().into_owned();
+			fields.insert("editor_binary_path".to_owned(), relative.into());
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct UnsupportedConfigVersion {
+	pub path: PathBuf,
+	pub version: u64,
+}
+impl std::fmt::Display for UnsupportedConfigVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.version {
+			0 => write!(
+				f,
+				"{} is version 0 of the config schema, which does not exist; set its version to {CONFIG_VERSION} or remove it",
+				self.path.display()
+			),
+			version => write!(
+				f,
+				"{} is version {version} of the config schema, but this uebuild only supports up to version {CONFIG_VERSION}; update uebuild",
+				self.path.display()
+			),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn migrates_unversioned_configs() {
+		let mut value = json!({
+			"engine_path": "/ue",
+			"editor_binary_path": "/ue/Binaries/Linux/UE4Editor",
+		});
+		let from = migrate(&mut value, Path::new("uebuild-cfg.json")).unwrap();
+		assert_eq!(from, Some(1));
+		assert_eq!(
+			value,
+			json!({
+				"version": CONFIG_VERSION,
+				"engine_path": "/ue",
+				"editor_binary_path": "Binaries/Linux/UE4Editor",
+			})
+		);
+	}
+
+	#[test]
+	fn rejects_unknown_versions() {
+		for version in [0, CONFIG_VERSION + 1] {
+			let mut value = json!({ "version": version });
+			let err = migrate(&mut value, Path::new("uebuild-cfg.json")).unwrap_err();
+			assert_eq!(err.version, version);
+		}
+	}
+}
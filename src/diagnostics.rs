@@ -0,0 +1,310 @@
+use regex::Regex;
+use serde::Serialize;
+use std::{
+	path::PathBuf,
+	sync::{LazyLock, Mutex},
+};
+
+mod annotations;
+pub use annotations::*;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+	Warning,
+	Error,
+}
+
+impl std::fmt::Display for Severity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Warning => write!(f, "warning"),
+			Self::Error => write!(f, "error"),
+		}
+	}
+}
+
+/// A warning or error reported in the output of a compiler, UnrealBuildTool, AutomationTool or the engine.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	/// What reported the diagnostic: a compiler code (e.g. `C2065`), a clang warning flag (e.g. `-Wunused-variable`),
+	/// a UE log category (e.g. `LogCook`), or `AutomationTool`.
+	pub category: String,
+	pub file: Option<PathBuf>,
+	pub line: Option<u32>,
+	pub message: String,
+}
+
+impl Diagnostic {
+	/// Parses a line of process output, returning the diagnostic it reports, if any.
+	///
+	/// Recognizes:
+	/// - MSVC: `File.cpp(12): error C2065: message`
+	/// - clang: `File.cpp:12:5: warning: message [-Wflag]`
+	/// - UE logs: `[timestamp][frame]LogInit: Error: message` (`Fatal` is reported as an error),
+	///   optionally prefixed by the editor executable as AutomationTool echoes them (`UE4Editor-Cmd: LogCook: ...`)
+	/// - AutomationTool: `ERROR: message`, `WARNING: message` and `BUILD FAILED`
+	pub fn parse(line: &str) -> Option<Self> {
+		static MSVC: LazyLock<Regex> = LazyLock::new(|| {
+			Regex::new(r"^\s*(?P<file>.+?)\((?P<line>\d+)(?:,\d+)?\)\s*:\s*(?P<severity>fatal error|error|warning)(?:\s+(?P<code>[A-Za-z]+\d+))?\s*:\s*(?P<message>.*)$").unwrap()
+		});
+		static CLANG: LazyLock<Regex> = LazyLock::new(|| {
+			Regex::new(r"^\s*(?P<file>(?:[A-Za-z]:)?[^:]+):(?P<line>\d+):(?:\d+:)?\s*(?P<severity>fatal error|error|warning):\s*(?P<message>.*?)(?:\s+\[(?P<flag>-W[^\]]+)\])?$").unwrap()
+		});
+		static UE_LOG: LazyLock<Regex> = LazyLock::new(|| {
+			Regex::new(
+				r"^\s*(?:\w+Editor(?:-Cmd)?:\s*)?(?:\[[^\]]*\])*\s*(?P<category>Log\w+):\s*(?P<severity>Fatal|Error|Warning):\s*(?P<message>.*)$",
+			)
+			.unwrap()
+		});
+		static UAT: LazyLock<Regex> = LazyLock::new(|| {
+			Regex::new(r"^\s*(?P<severity>ERROR|WARNING):\s*(?P<message>.*)$").unwrap()
+		});
+
+		if let Some(captures) = MSVC.captures(line).or_else(|| CLANG.captures(line)) {
+			let category = captures
+				.name("code")
+				.or_else(|| captures.name("flag"))
+				.map(|code| code.as_str().to_owned())
+				.unwrap_or_else(|| "compiler".to_owned());
+			return Some(Self {
+				severity: match &captures["severity"] {
+					"warning" => Severity::Warning,
+					_ => Severity::Error,
+				},
+				category,
+				file: Some(PathBuf::from(captures["file"].trim())),
+				line: captures["line"].parse().ok(),
+				message: captures["message"].trim().to_owned(),
+			});
+		}
+		if let Some(captures) = UE_LOG.captures(line) {
+			return Some(Self {
+				severity: match &captures["severity"] {
+					"Warning" => Severity::Warning,
+					_ => Severity::Error,
+				},
+				category: captures["category"].to_owned(),
+				file: None,
+				line: None,
+				message: captures["message"].trim().to_owned(),
+			});
+		}
+		if let Some(captures) = UAT.captures(line) {
+			return Some(Self {
+				severity: match &captures["severity"] {
+					"WARNING" => Severity::Warning,
+					_ => Severity::Error,
+				},
+				category: "AutomationTool".to_owned(),
+				file: None,
+				line: None,
+				message: captures["message"].trim().to_owned(),
+			});
+		}
+		if line.contains("BUILD FAILED") {
+			return Some(Self {
+				severity: Severity::Error,
+				category: "AutomationTool".to_owned(),
+				file: None,
+				line: None,
+				message: line.trim().to_owned(),
+			});
+		}
+		None
+	}
+}
+
+impl std::fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match (&self.file, self.line) {
+			(Some(file), Some(line)) => write!(f, "{}({line}): ", file.display())?,
+			(Some(file), None) => write!(f, "{}: ", file.display())?,
+			_ => {}
+		}
+		write!(f, "{} {}: {}", self.severity, self.category, self.message)
+	}
+}
+
+/// The number of warnings and errors listed by a [`DiagnosticSummary`], before the rest are only counted.
+const SUMMARY_LIST_LENGTH: usize = 20;
+
+/// Collects the diagnostics from the output of a process, ignoring duplicates
+/// (UnrealBuildTool often repeats a compiler's errors when the build fails).
+#[derive(Debug, Default)]
+pub struct DiagnosticCollector(Mutex<Vec<Diagnostic>>);
+
+impl DiagnosticCollector {
+	/// Parses a line of output, returning the diagnostic it reported if it has not been seen before.
+	pub fn push_line(&self, line: &str) -> Option<Diagnostic> {
+		let diagnostic = Diagnostic::parse(line)?;
+		let mut diagnostics = self.0.lock().unwrap();
+		if diagnostics.contains(&diagnostic) {
+			return None;
+		}
+		diagnostics.push(diagnostic.clone());
+		Some(diagnostic)
+	}
+
+	pub fn into_inner(self) -> Vec<Diagnostic> {
+		self.0.into_inner().unwrap()
+	}
+}
+
+/// The end-of-run report of the warnings and errors a process emitted.
+pub struct DiagnosticSummary<'a>(pub &'a [Diagnostic]);
+
+impl DiagnosticSummary<'_> {
+	fn count(&self, severity: Severity) -> usize {
+		self.0
+			.iter()
+			.filter(|diagnostic| diagnostic.severity == severity)
+			.count()
+	}
+}
+
+impl std::fmt::Display for DiagnosticSummary<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let errors = self.count(Severity::Error);
+		let warnings = self.count(Severity::Warning);
+		write!(f, "{errors} errors, {warnings} warnings")?;
+		for severity in [Severity::Error, Severity::Warning] {
+			let mut diagnostics = self
+				.0
+				.iter()
+				.filter(|diagnostic| diagnostic.severity == severity);
+			for diagnostic in diagnostics.by_ref().take(SUMMARY_LIST_LENGTH) {
+				write!(f, "\n  {diagnostic}")?;
+			}
+			let remaining = diagnostics.count();
+			if remaining > 0 {
+				write!(f, "\n  ... and {remaining} more {severity}s")?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn diagnostic(
+		severity: Severity,
+		category: &str,
+		location: Option<(&str, u32)>,
+		message: &str,
+	) -> Diagnostic {
+		Diagnostic {
+			severity,
+			category: category.to_owned(),
+			file: location.map(|(file, _)| PathBuf::from(file)),
+			line: location.map(|(_, line)| line),
+			message: message.to_owned(),
+		}
+	}
+
+	#[test]
+	fn parses_tool_output() {
+		use Severity::{Error, Warning};
+		let cases = [
+			(
+				r"D:\Game\Source\Game\Player.cpp(42): error C2065: 'Health': undeclared identifier",
+				Some(diagnostic(Error, "C2065", Some((r"D:\Game\Source\Game\Player.cpp", 42)), "'Health': undeclared identifier")),
+			),
+			(
+				r"D:\Game\Source\Game\Player.h(7,12): fatal error C1083: Cannot open include file: 'Weapon.h': No such file or directory",
+				Some(diagnostic(Error, "C1083", Some((r"D:\Game\Source\Game\Player.h", 7)), "Cannot open include file: 'Weapon.h': No such file or directory")),
+			),
+			(
+				"/home/dev/Game/Source/Game/Player.cpp:42:13: warning: unused variable 'Speed' [-Wunused-variable]",
+				Some(diagnostic(Warning, "-Wunused-variable", Some(("/home/dev/Game/Source/Game/Player.cpp", 42)), "unused variable 'Speed'")),
+			),
+			(
+				"/home/dev/Game/Source/Game/Player.cpp:50:1: error: expected ';' after class",
+				Some(diagnostic(Error, "compiler", Some(("/home/dev/Game/Source/Game/Player.cpp", 50)), "expected ';' after class")),
+			),
+			(
+				"ERROR: UnrealBuildTool failed. See log for more details. (/home/dev/Library/Logs/Unreal Engine/LocalBuildLogs/UBT-GameEditor-Linux-Development.txt)",
+				Some(diagnostic(Error, "AutomationTool", None, "UnrealBuildTool failed. See log for more details. (/home/dev/Library/Logs/Unreal Engine/LocalBuildLogs/UBT-GameEditor-Linux-Development.txt)")),
+			),
+			(
+				"WARNING: Unable to find UBT log file.",
+				Some(diagnostic(Warning, "AutomationTool", None, "Unable to find UBT log file.")),
+			),
+			(
+				"BUILD FAILED: failed, retries not enabled:",
+				Some(diagnostic(Error, "AutomationTool", None, "BUILD FAILED: failed, retries not enabled:")),
+			),
+			(
+				"[2024.01.31-17.05.12:345][  0]LogCook: Warning: Unable to find package for cooking /Game/Maps/Missing",
+				Some(diagnostic(Warning, "LogCook", None, "Unable to find package for cooking /Game/Maps/Missing")),
+			),
+			(
+				"UE4Editor-Cmd: [2024.01.31-17.05.13:001][ 12]LogLinker: Error: Failed to load '/Game/Weapons/BP_Rifle': Can't find file.",
+				Some(diagnostic(Error, "LogLinker", None, "Failed to load '/Game/Weapons/BP_Rifle': Can't find file.")),
+			),
+			(
+				"LogWindows: Fatal: Assertion failed: IsValid(Actor)",
+				Some(diagnostic(Error, "LogWindows", None, "Assertion failed: IsValid(Actor)")),
+			),
+			// A UAT warning which quotes an engine log line is still reported by UAT
+			(
+				"WARNING: Commandlet reported LogCook: Error: Content is missing from cook",
+				Some(diagnostic(Warning, "AutomationTool", None, "Commandlet reported LogCook: Error: Content is missing from cook")),
+			),
+			// Log lines quoted in the middle of other output are not diagnostics
+			("Running: Editor -run=Cook -NoLogTimes LogCook: Error: are fatal", None),
+			("[2024.01.31-17.05.12:345][  0]LogInit: Display: LogCook: Error: 0 errors", None),
+			("[2024.01.31-17.05.12:345][  0]LogInit: Display: Engine is initialized.", None),
+			("[12/340] Compile Module.Game.cpp", None),
+			("AutomationTool exiting with ExitCode=0 (Success)", None),
+		];
+		for (line, expected) in cases.into_iter() {
+			assert_eq!(Diagnostic::parse(line), expected, "{line}");
+		}
+	}
+
+	#[test]
+	fn collector_ignores_duplicates() {
+		let collector = DiagnosticCollector::default();
+		let line = "Player.cpp(42): error C2065: 'Health': undeclared identifier";
+		assert!(collector.push_line(line).is_some());
+		assert!(collector.push_line("Compiling Player.cpp").is_none());
+		assert!(collector.push_line(line).is_none());
+		assert!(collector
+			.push_line("Player.cpp(43): error C2065: 'Health': undeclared identifier")
+			.is_some());
+		assert_eq!(collector.into_inner().len(), 2);
+	}
+
+	#[test]
+	fn summary_lists_a_limited_number_per_severity() {
+		let mut diagnostics = Vec::new();
+		for index in 0..SUMMARY_LIST_LENGTH + 3 {
+			diagnostics.push(diagnostic(
+				Severity::Error,
+				"C2065",
+				None,
+				&format!("error {index}"),
+			));
+		}
+		diagnostics.push(diagnostic(Severity::Warning, "LogCook", None, "warning"));
+		let summary = DiagnosticSummary(&diagnostics).to_string();
+		let lines = summary.lines().collect::<Vec<_>>();
+		assert_eq!(
+			lines[0],
+			format!("{} errors, 1 warnings", SUMMARY_LIST_LENGTH + 3)
+		);
+		assert_eq!(lines[1], "  error C2065: error 0");
+		assert_eq!(
+			lines[SUMMARY_LIST_LENGTH],
+			format!("  error C2065: error {}", SUMMARY_LIST_LENGTH - 1)
+		);
+		assert_eq!(lines[SUMMARY_LIST_LENGTH + 1], "  ... and 3 more errors");
+		assert_eq!(lines[SUMMARY_LIST_LENGTH + 2], "  warning LogCook: warning");
+		assert_eq!(lines.len(), SUMMARY_LIST_LENGTH + 3);
+	}
+}
@@ -0,0 +1,199 @@
+use super::{Diagnostic, Severity};
+use crate::{
+	output::OutputFormat,
+	runner::{OutputStream, ProcessObserver},
+};
+use anyhow::Context;
+use clap::ValueEnum;
+use std::{path::PathBuf, process::ExitStatus, sync::Mutex};
+
+/// How diagnostics are reported to a CI system, so that it can show them against the source.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AnnotationFormat {
+	/// GitHub Actions `::error file=...,line=...::message` workflow commands, printed as diagnostics are found.
+	Github,
+	/// TeamCity `##teamcity[...]` service messages, printed as diagnostics are found.
+	Teamcity,
+	/// A JUnit XML report with one failing test case per error, rewritten after each command.
+	Junit,
+}
+
+/// Reports the diagnostics found in process output in an [`AnnotationFormat`].
+///
+/// Annotations are printed to stdout, or to stderr with `--output json` so that stdout only holds events.
+#[derive(Debug)]
+pub struct Annotator {
+	format: AnnotationFormat,
+	junit_path: PathBuf,
+	output: OutputFormat,
+	/// The command line and diagnostics of each command that has finished.
+	finished: Mutex<Vec<(String, Vec<Diagnostic>)>>,
+}
+
+impl Annotator {
+	pub fn new(format: AnnotationFormat, junit_path: PathBuf, output: OutputFormat) -> Self {
+		Self {
+			format,
+			junit_path,
+			output,
+			finished: Mutex::new(Vec::new()),
+		}
+	}
+}
+
+impl ProcessObserver for Annotator {
+	/// Reports a diagnostic as soon as it is found in the output.
+	fn output(&self, _stream: OutputStream, _line: &str, diagnostic: Option<&Diagnostic>) {
+		let Some(diagnostic) = diagnostic else {
+			return;
+		};
+		let annotation = match self.format {
+			AnnotationFormat::Github => github_command(diagnostic),
+			AnnotationFormat::Teamcity => teamcity_message(diagnostic),
+			AnnotationFormat::Junit => return,
+		};
+		match self.output {
+			OutputFormat::Text => println!("{annotation}"),
+			OutputFormat::Json => eprintln!("{annotation}"),
+		}
+	}
+
+	/// Records the diagnostics of a command which has finished, and rewrites the JUnit report.
+	fn finished(
+		&self,
+		command_line: &str,
+		_status: &ExitStatus,
+		diagnostics: &[Diagnostic],
+	) -> anyhow::Result<()> {
+		let mut finished = self.finished.lock().unwrap();
+		finished.push((command_line.to_owned(), diagnostics.to_vec()));
+		if self.format == AnnotationFormat::Junit {
+			if let Some(dir) = self
+				.junit_path
+				.parent()
+				.filter(|dir| !dir.as_os_str().is_empty())
+			{
+				std::fs::create_dir_all(dir).context("write JUnit report")?;
+			}
+			std::fs::write(&self.junit_path, junit_report(&finished))
+				.context("write JUnit report")?;
+			tracing::info!("Wrote JUnit report to {}", self.junit_path.display());
+		}
+		Ok(())
+	}
+}
+
+fn github_command(diagnostic: &Diagnostic) -> String {
+	fn escape_data(value: &str) -> String {
+		value
+			.replace('%', "%25")
+			.replace('\r', "%0D")
+			.replace('\n', "%0A")
+	}
+	fn escape_property(value: &str) -> String {
+		escape_data(value).replace(':', "%3A").replace(',', "%2C")
+	}
+	let command = match diagnostic.severity {
+		Severity::Warning => "warning",
+		Severity::Error => "error",
+	};
+	let mut properties = Vec::new();
+	if let Some(file) = &diagnostic.file {
+		properties.push(format!("file={}", escape_property(&file.to_string_lossy())));
+	}
+	if let Some(line) = diagnostic.line {
+		properties.push(format!("line={line}"));
+	}
+	properties.push(format!("title={}", escape_property(&diagnostic.category)));
+	format!(
+		"::{command} {}::{}",
+		properties.join(","),
+		escape_data(&diagnostic.message)
+	)
+}
+
+fn teamcity_message(diagnostic: &Diagnostic) -> String {
+	fn escape(value: &str) -> String {
+		let mut escaped = String::with_capacity(value.len());
+		for c in value.chars() {
+			match c {
+				'|' => escaped.push_str("||"),
+				'\'' => escaped.push_str("|'"),
+				'\n' => escaped.push_str("|n"),
+				'\r' => escaped.push_str("|r"),
+				'[' => escaped.push_str("|["),
+				']' => escaped.push_str("|]"),
+				c => escaped.push(c),
+			}
+		}
+		escaped
+	}
+	match diagnostic.severity {
+		Severity::Error => format!(
+			"##teamcity[buildProblem description='{}']",
+			escape(&diagnostic.to_string())
+		),
+		Severity::Warning => format!(
+			"##teamcity[message text='{}' status='WARNING']",
+			escape(&diagnostic.to_string())
+		),
+	}
+}
+
+fn junit_report(finished: &[(String, Vec<Diagnostic>)]) -> String {
+	fn escape(value: &str) -> String {
+		value
+			.replace('&', "&amp;")
+			.replace('<', "&lt;")
+			.replace('>', "&gt;")
+			.replace('"', "&quot;")
+			.replace('\'', "&apos;")
+	}
+	let mut xml =
+		String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"uebuild\">\n");
+	for (command_line, diagnostics) in finished.iter() {
+		let errors = diagnostics
+			.iter()
+			.filter(|diagnostic| diagnostic.severity == Severity::Error)
+			.collect::<Vec<_>>();
+		// A command without errors is reported as a single passing case
+		let tests = errors.len().max(1);
+		xml.push_str(&format!(
+			"  <testsuite name=\"{}\" tests=\"{tests}\" failures=\"{}\">\n",
+			escape(command_line),
+			errors.len()
+		));
+		if errors.is_empty() {
+			xml.push_str(&format!(
+				"    <testcase name=\"{}\" classname=\"uebuild\"/>\n",
+				escape(command_line)
+			));
+		}
+		for error in errors.into_iter() {
+			let name = match (&error.file, error.line) {
+				(Some(file), Some(line)) => format!("{}({line})", file.display()),
+				(Some(file), None) => format!("{}", file.display()),
+				_ => error.message.clone(),
+			};
+			xml.push_str(&format!(
+				"    <testcase name=\"{}\" classname=\"{}\"",
+				escape(&name),
+				escape(&error.category)
+			));
+			if let Some(file) = &error.file {
+				xml.push_str(&format!(" file=\"{}\"", escape(&file.to_string_lossy())));
+			}
+			if let Some(line) = error.line {
+				xml.push_str(&format!(" line=\"{line}\""));
+			}
+			xml.push_str(&format!(
+				">\n      <failure message=\"{}\" type=\"error\">{}</failure>\n    </testcase>\n",
+				escape(&error.message),
+				escape(&error.to_string())
+			));
+		}
+		xml.push_str("  </testsuite>\n");
+	}
+	xml.push_str("</testsuites>\n");
+	xml
+}
@@ -0,0 +1,53 @@
+use crate::{config::LoadConfigError, config::MissingValue, utility::ProcessFailed};
+
+/// The status codes that uebuild exits with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExitCode {
+	/// The requested operation completed.
+	Success,
+	/// The operation failed for a reason not covered by a more specific code.
+	Failure,
+	/// The command line arguments were invalid (e.g. unknown arguments or invalid values).
+	Usage,
+	/// A child process (e.g. `Build.bat` or `RunUAT.bat`) exited with a non-zero status.
+	ProcessFailed,
+	/// The configuration could not be loaded or is missing a required value.
+	Config,
+	/// The run was aborted by a terminate signal (CTRL+C).
+	Interrupted,
+}
+
+impl ExitCode {
+	pub fn code(&self) -> u8 {
+		match self {
+			Self::Success => 0,
+			Self::Failure => 1,
+			Self::Usage => 2,
+			Self::ProcessFailed => 3,
+			Self::Config => 4,
+			Self::Interrupted => 130,
+		}
+	}
+
+	/// Determines the exit code which best describes the provided error.
+	pub fn from_error(error: &anyhow::Error) -> Self {
+		if error.downcast_ref::<clap::Error>().is_some() {
+			return Self::Usage;
+		}
+		if error.downcast_ref::<ProcessFailed>().is_some() {
+			return Self::ProcessFailed;
+		}
+		if error.downcast_ref::<LoadConfigError>().is_some()
+			|| error.downcast_ref::<MissingValue>().is_some()
+		{
+			return Self::Config;
+		}
+		Self::Failure
+	}
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+	fn from(code: ExitCode) -> Self {
+		Self::from(code.code())
+	}
+}
@@ -5,13 +5,24 @@ mod runtime;
 pub use runtime::*;
 mod plugin;
 pub use plugin::*;
+mod exit_code;
+pub use exit_code::*;
+pub mod session;
+pub use session::Session;
 
 pub mod config;
+pub mod diagnostics;
+pub mod logging;
+pub mod output;
+pub mod run_log;
+pub mod runner;
 pub use config::Config;
+#[cfg(test)]
+mod test_support;
 pub mod types;
 pub mod unreal;
 pub mod utility;
 
 pub trait Operation {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>>;
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>>;
 }
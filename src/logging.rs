@@ -0,0 +1,77 @@
+use crate::output::OutputFormat;
+use std::io::IsTerminal;
+use tracing::Level;
+use tracing_subscriber::{fmt::writer::MakeWriterExt, EnvFilter};
+
+/// The environment variable which holds a `RUST_LOG`-style filter (e.g. `uebuild=debug`), overriding `-v`/`-q`.
+pub const LOG_FILTER_ENV_VAR: &str = "UEBUILD_LOG";
+
+/// The levels selected by each `-v` or `-q` flag, where `INFO` is the level without any flags.
+const LEVELS: [Level; 5] = [
+	Level::ERROR,
+	Level::WARN,
+	Level::INFO,
+	Level::DEBUG,
+	Level::TRACE,
+];
+const DEFAULT_LEVEL: usize = 2;
+
+/// How much uebuild reports while it runs.
+///
+/// Output of child processes is logged at `INFO` (stdout, target `process::stdout`) and `WARN` (stderr, target `process::stderr`)
+/// inside a `process` span, so each line is tagged with the command that produced it.
+/// Operations run inside a `command` span naming the subcommand.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+	/// The number of `-v` flags.
+	pub verbose: u8,
+	/// The number of `-q` flags.
+	pub quiet: u8,
+	/// The filter from `--log-filter`, `UEBUILD_LOG` or `RUST_LOG`.
+	pub filter: Option<String>,
+}
+
+impl LogOptions {
+	/// The maximum level of events to report when there is no filter.
+	pub fn level(&self) -> Level {
+		let index = DEFAULT_LEVEL + self.verbose as usize;
+		LEVELS[index
+			.saturating_sub(self.quiet as usize)
+			.min(LEVELS.len() - 1)]
+	}
+
+	/// Installs the global subscriber, which writes warnings and errors to stderr and everything else to stdout.
+	/// With `--output json`, stdout is reserved for events so everything is written to stderr.
+	/// Timestamps and event targets are included when `-v` is provided.
+	pub fn init(&self, output: OutputFormat) {
+		let level_filter = EnvFilter::new(self.level().as_str());
+		let (filter, invalid_filter) = match &self.filter {
+			Some(directives) => match EnvFilter::try_new(directives) {
+				Ok(filter) => (filter, None),
+				Err(err) => (level_filter, Some((directives, err))),
+			},
+			None => (level_filter, None),
+		};
+		let max_stderr_level = match output {
+			OutputFormat::Text => Level::WARN,
+			OutputFormat::Json => Level::TRACE,
+		};
+		let writer = std::io::stderr
+			.with_max_level(max_stderr_level)
+			.or_else(std::io::stdout);
+		let builder = tracing_subscriber::fmt()
+			.with_env_filter(filter)
+			.with_writer(writer)
+			.with_ansi(std::io::stdout().is_terminal() && std::io::stderr().is_terminal());
+		let result = match self.verbose {
+			0 => builder.without_time().with_target(false).try_init(),
+			_ => builder.try_init(),
+		};
+		// A subscriber may already be installed when uebuild is used as a library
+		if result.is_ok() {
+			if let Some((directives, err)) = invalid_filter {
+				tracing::warn!("Ignoring invalid log filter {directives:?}: {err}");
+			}
+		}
+	}
+}
@@ -1,10 +1,11 @@
 use uebuild::{cli::Cli, package, unreal::Unreal, Runtime};
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> std::process::ExitCode {
 	Runtime::new(package!())
 		.with_plugin(Cli)
 		.with_plugin(Unreal)
 		.run()
 		.await
+		.into()
 }
@@ -0,0 +1,139 @@
+use crate::{
+	diagnostics::{Diagnostic, Severity},
+	runner::{OutputStream, ProcessObserver},
+};
+use regex::Regex;
+use serde::Serialize;
+use std::{path::Path, process::ExitStatus, sync::LazyLock};
+
+/// How uebuild reports to scripts and users, selected by `--output`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+	/// Human-readable text.
+	#[default]
+	Text,
+	/// A stream of events on stdout, one JSON object per line.
+	/// Logs are written to stderr instead.
+	Json,
+}
+
+/// An event in the `--output json` stream, written as a JSON object whose `event` field names the variant.
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+	/// The command line was parsed and the operation is about to run.
+	CommandStarted { command: &'a str, args: Vec<String> },
+	/// A child process was spawned.
+	ProcessStarted {
+		command_line: &'a str,
+		current_dir: Option<&'a Path>,
+	},
+	/// A line that a child process wrote which is not a progress update.
+	Output { stream: OutputStream, line: &'a str },
+	/// A `[current/total]` progress line from UnrealBuildTool or AutomationTool.
+	Progress {
+		current: u32,
+		total: u32,
+		message: &'a str,
+	},
+	/// A warning or error found in the output of a child process.
+	Diagnostic(&'a Diagnostic),
+	/// A child process exited.
+	ProcessFinished {
+		command_line: &'a str,
+		success: bool,
+		/// `None` if the process was ended by a signal.
+		exit_code: Option<i32>,
+		errors: usize,
+		warnings: usize,
+	},
+	/// A change that `--dry-run` would have made.
+	DryRun { action: String },
+	/// The document produced by a command which reports or lists something (e.g. `cfg`, `plugin list`).
+	Data { data: serde_json::Value },
+	/// The operation has returned, whether or not it succeeded.
+	CommandFinished {
+		command: &'a str,
+		success: bool,
+		duration_ms: u128,
+	},
+	/// The run has ended; always the last event.
+	Result {
+		success: bool,
+		exit_code: u8,
+		duration_ms: u128,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		error: Option<String>,
+	},
+}
+
+impl Event<'_> {
+	/// Writes the event to stdout as a single line.
+	pub fn emit(&self) {
+		match serde_json::to_string(self) {
+			Ok(json) => println!("{json}"),
+			Err(err) => tracing::error!("Failed to serialize {self:?}: {err}"),
+		}
+	}
+}
+
+/// Emits the document produced by a reporting or listing command as a [`Event::Data`].
+pub fn emit_data(data: &impl Serialize) -> anyhow::Result<()> {
+	let data = serde_json::to_value(data)?;
+	Event::Data { data }.emit();
+	Ok(())
+}
+
+/// Reports the activity of child processes as [events](Event) for `--output json`.
+#[derive(Debug, Default)]
+pub struct JsonEvents;
+
+impl ProcessObserver for JsonEvents {
+	fn started(&self, command_line: &str, current_dir: Option<&Path>) {
+		Event::ProcessStarted {
+			command_line,
+			current_dir,
+		}
+		.emit();
+	}
+
+	fn output(&self, stream: OutputStream, line: &str, diagnostic: Option<&Diagnostic>) {
+		static PROGRESS: LazyLock<Regex> = LazyLock::new(|| {
+			Regex::new(r"^\s*\[(?P<current>\d+)/(?P<total>\d+)\]\s*(?P<message>.*)$").unwrap()
+		});
+		let progress = PROGRESS.captures(line).and_then(|captures| {
+			Some(Event::Progress {
+				current: captures["current"].parse().ok()?,
+				total: captures["total"].parse().ok()?,
+				message: captures.name("message")?.as_str(),
+			})
+		});
+		progress.unwrap_or(Event::Output { stream, line }).emit();
+		if let Some(diagnostic) = diagnostic {
+			Event::Diagnostic(diagnostic).emit();
+		}
+	}
+
+	fn finished(
+		&self,
+		command_line: &str,
+		status: &ExitStatus,
+		diagnostics: &[Diagnostic],
+	) -> anyhow::Result<()> {
+		let count = |severity| {
+			diagnostics
+				.iter()
+				.filter(|diagnostic| diagnostic.severity == severity)
+				.count()
+		};
+		Event::ProcessFinished {
+			command_line,
+			success: status.success(),
+			exit_code: status.code(),
+			errors: count(Severity::Error),
+			warnings: count(Severity::Warning),
+		}
+		.emit();
+		Ok(())
+	}
+}
@@ -1,10 +1,13 @@
-use crate::{utility::PinFuture, Config, Runtime};
+use crate::{utility::PinFuture, Runtime, Session};
 
 pub trait Plugin {
-	fn add_subcommands(&self, runtime: &mut Runtime, config: &Config);
+	/// Adds the plugin's subcommands to the runtime's command.
+	/// The session's runner may be replaced (e.g. by `--dry-run`) before any operation runs.
+	/// This is also called with an empty config before the config is loaded, to find the global arguments.
+	fn add_subcommands(&self, runtime: &mut Runtime, session: &Session);
 	fn run(
 		&self,
 		matches: &clap::ArgMatches,
-		config: &mut Option<Config>,
+		session: &Session,
 	) -> Option<PinFuture<anyhow::Result<()>>>;
 }
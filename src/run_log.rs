@@ -0,0 +1,198 @@
+use crate::{
+	diagnostics::Diagnostic,
+	runner::{OutputStream, ProcessObserver},
+	ExitCode,
+};
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use std::{
+	fs::File,
+	io::{LineWriter, Write},
+	path::{Path, PathBuf},
+	process::ExitStatus,
+	sync::Mutex,
+};
+
+/// The prefix and extension of run log file names (e.g. `uebuild-2024.01.31-17.05.12.345.log`).
+const FILE_PREFIX: &str = "uebuild-";
+const FILE_EXTENSION: &str = "log";
+/// Lines of the log header which are read back by [`RunLogInfo`].
+const COMMAND_LINE_HEADER: &str = "Command line: ";
+const EXIT_STATUS_HEADER: &str = "Exit status: ";
+
+/// Environment variables whose values are not written to run logs, matched against any part of the name.
+const REDACTED_ENV_VARS: [&str; 6] = ["TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "KEY"];
+
+/// A log of a single uebuild invocation: the command line, environment, start and end times, exit status,
+/// and the interleaved output of every child process with timestamps.
+pub struct RunLog {
+	path: PathBuf,
+	file: Mutex<LineWriter<File>>,
+}
+
+impl RunLog {
+	/// Creates a new log in the directory and writes its header,
+	/// deleting the oldest logs so that at most `retention` remain (`0` keeps every log).
+	pub fn create(dir: &Path, retention: usize) -> anyhow::Result<Self> {
+		std::fs::create_dir_all(dir).context(format!("create {}", dir.display()))?;
+		if retention > 0 {
+			let logs = list_run_logs(dir)?;
+			let excess = (logs.len() + 1).saturating_sub(retention);
+			for path in logs.into_iter().take(excess) {
+				std::fs::remove_file(&path)
+					.context(format!("remove old log {}", path.display()))?;
+			}
+		}
+
+		let started = Local::now();
+		let file_name = format!(
+			"{FILE_PREFIX}{}.{FILE_EXTENSION}",
+			started.format("%Y.%m.%d-%H.%M.%S%.3f")
+		);
+		let path = dir.join(file_name);
+		let file = File::create(&path).context(format!("create {}", path.display()))?;
+		let log = Self {
+			path,
+			file: Mutex::new(LineWriter::new(file)),
+		};
+
+		let command_line = std::env::args()
+			.map(|arg| match arg.contains(char::is_whitespace) {
+				true => format!("{arg:?}"),
+				false => arg,
+			})
+			.collect::<Vec<_>>();
+		log.write(format!("{COMMAND_LINE_HEADER}{}", command_line.join(" ")));
+		if let Ok(dir) = std::env::current_dir() {
+			log.write(format!("Working directory: {}", dir.display()));
+		}
+		log.write(format!("Started: {}", started.to_rfc3339()));
+		log.write("Environment:");
+		let mut vars = std::env::vars_os().collect::<Vec<_>>();
+		vars.sort();
+		for (name, value) in vars.into_iter() {
+			let name = name.to_string_lossy();
+			let redact = REDACTED_ENV_VARS
+				.iter()
+				.any(|part| name.to_uppercase().contains(part));
+			match redact {
+				true => log.write(format!("  {name}=<redacted>")),
+				false => log.write(format!("  {name}={}", value.to_string_lossy())),
+			}
+		}
+		log.write("");
+		Ok(log)
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	fn write(&self, line: impl AsRef<str>) {
+		// Failing to log must never fail the run
+		let _ = writeln!(self.file.lock().unwrap(), "{}", line.as_ref());
+	}
+
+	fn write_timestamped(&self, line: impl AsRef<str>) {
+		let now = Local::now().format("%H:%M:%S%.3f");
+		self.write(format!("[{now}] {}", line.as_ref()));
+	}
+
+	/// Writes the end time and exit status of the run.
+	pub fn finish(&self, result: &anyhow::Result<()>) {
+		self.write("");
+		self.write(format!("Ended: {}", Local::now().to_rfc3339()));
+		match result {
+			Ok(()) => self.write(format!("{EXIT_STATUS_HEADER}{}", ExitCode::Success.code())),
+			Err(err) => {
+				let code = ExitCode::from_error(err);
+				self.write(format!("{EXIT_STATUS_HEADER}{}", code.code()));
+				self.write(format!("Error: {}", err.root_cause()));
+			}
+		}
+	}
+}
+
+impl ProcessObserver for RunLog {
+	fn started(&self, command_line: &str, current_dir: Option<&Path>) {
+		match current_dir {
+			Some(dir) => {
+				self.write_timestamped(format!("Executing \"{command_line}\" in {}", dir.display()))
+			}
+			None => self.write_timestamped(format!("Executing \"{command_line}\"")),
+		}
+	}
+
+	fn output(&self, stream: OutputStream, line: &str, _diagnostic: Option<&Diagnostic>) {
+		self.write_timestamped(format!("[{stream}] {line}"));
+	}
+
+	fn finished(
+		&self,
+		command_line: &str,
+		status: &ExitStatus,
+		diagnostics: &[Diagnostic],
+	) -> anyhow::Result<()> {
+		self.write_timestamped(format!(
+			"\"{command_line}\" finished with {status} ({} diagnostics)",
+			diagnostics.len()
+		));
+		Ok(())
+	}
+}
+
+/// The run logs in the directory, oldest first.
+pub fn list_run_logs(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+	if !dir.exists() {
+		return Ok(Vec::new());
+	}
+	let mut logs = Vec::new();
+	for entry in std::fs::read_dir(dir).context(format!("read {}", dir.display()))? {
+		let path = entry?.path();
+		let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+			continue;
+		};
+		if file_name.starts_with(FILE_PREFIX)
+			&& path.extension().and_then(|ext| ext.to_str()) == Some(FILE_EXTENSION)
+		{
+			logs.push(path);
+		}
+	}
+	// Timestamps in file names sort chronologically
+	logs.sort();
+	Ok(logs)
+}
+
+/// The summary of a run log, read from its header and footer.
+#[derive(Clone, Debug)]
+pub struct RunLogInfo {
+	pub path: PathBuf,
+	pub command_line: Option<String>,
+	/// The exit status of the run, or `None` if it has not finished (or was interrupted).
+	pub exit_status: Option<u8>,
+	pub started: Option<DateTime<Local>>,
+}
+
+impl RunLogInfo {
+	pub fn read(path: &Path) -> anyhow::Result<Self> {
+		let text = std::fs::read_to_string(path).context(format!("read {}", path.display()))?;
+		let mut info = Self {
+			path: path.to_owned(),
+			command_line: None,
+			exit_status: None,
+			started: None,
+		};
+		for line in text.lines() {
+			if let Some(command_line) = line.strip_prefix(COMMAND_LINE_HEADER) {
+				info.command_line.get_or_insert(command_line.to_owned());
+			} else if let Some(started) = line.strip_prefix("Started: ") {
+				info.started = DateTime::parse_from_rfc3339(started)
+					.ok()
+					.map(|time| time.with_timezone(&Local));
+			} else if let Some(status) = line.strip_prefix(EXIT_STATUS_HEADER) {
+				info.exit_status = status.parse().ok();
+			}
+		}
+		Ok(info)
+	}
+}
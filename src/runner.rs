@@ -0,0 +1,476 @@
+use crate::{
+	diagnostics::Diagnostic,
+	output::{Event, OutputFormat},
+	utility::{spawn_command, PinFutureLifetime, ProcessFailed},
+};
+use anyhow::Context;
+use std::{
+	collections::VecDeque,
+	path::{Path, PathBuf},
+	process::ExitStatus,
+	sync::{Arc, Mutex},
+};
+use tokio::process::Command;
+
+/// Executes the child processes and filesystem changes that operations request.
+///
+/// Operations never spawn processes or write files themselves, which allows the runner to be swapped out
+/// (e.g. for a [`RecordingRunner`] or [`DryRunRunner`]) when the workspace should not be touched.
+pub trait CommandRunner {
+	/// Runs the command to completion.
+	/// Fails with [`ProcessFailed`] if the command exits with a non-zero status.
+	fn spawn<'a>(&'a self, command: &'a mut Command) -> PinFutureLifetime<'a, anyhow::Result<()>>;
+
+	/// Performs the change to the filesystem.
+	fn apply(&self, mutation: FileMutation) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		Box::pin(mutation.apply())
+	}
+
+	fn write_file(
+		&self,
+		path: PathBuf,
+		contents: Vec<u8>,
+	) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.apply(FileMutation::Write { path, contents })
+	}
+
+	fn copy_file(&self, from: PathBuf, to: PathBuf) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.apply(FileMutation::Copy { from, to })
+	}
+
+	fn remove_file(&self, path: PathBuf) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.apply(FileMutation::RemoveFile(path))
+	}
+
+	fn create_dir_all(&self, path: PathBuf) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.apply(FileMutation::CreateDir(path))
+	}
+
+	fn remove_dir_all(&self, path: PathBuf) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.apply(FileMutation::RemoveDir(path))
+	}
+}
+
+/// The runner shared by the runtime and all operations.
+pub type SharedRunner = Arc<dyn CommandRunner + 'static + Send + Sync>;
+
+/// A change to the filesystem requested through a [`CommandRunner`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileMutation {
+	/// Create or truncate the file at `path` and fill it with `contents`.
+	Write {
+		path: PathBuf,
+		contents: Vec<u8>,
+	},
+	/// Copy the contents of `from` to `to`, replacing any existing file.
+	Copy {
+		from: PathBuf,
+		to: PathBuf,
+	},
+	RemoveFile(PathBuf),
+	/// Create the directory and all of its missing parents.
+	CreateDir(PathBuf),
+	/// Remove the directory and everything in it.
+	RemoveDir(PathBuf),
+}
+
+impl FileMutation {
+	pub async fn apply(self) -> anyhow::Result<()> {
+		let context = self.to_string();
+		match self {
+			Self::Write { path, contents } => tokio::fs::write(&path, contents).await,
+			Self::Copy { from, to } => tokio::fs::copy(&from, &to).await.map(|_| ()),
+			Self::RemoveFile(path) => tokio::fs::remove_file(&path).await,
+			Self::CreateDir(path) => tokio::fs::create_dir_all(&path).await,
+			Self::RemoveDir(path) => tokio::fs::remove_dir_all(&path).await,
+		}
+		.context(context)
+	}
+}
+
+impl std::fmt::Display for FileMutation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let display = |path: &Path| path.display().to_string();
+		match self {
+			Self::Write { path, contents } => {
+				write!(f, "write {} bytes to {}", contents.len(), display(path))
+			}
+			Self::Copy { from, to } => write!(f, "copy {} to {}", display(from), display(to)),
+			Self::RemoveFile(path) => write!(f, "remove file {}", display(path)),
+			Self::CreateDir(path) => write!(f, "create directory {}", display(path)),
+			Self::RemoveDir(path) => write!(f, "remove directory {}", display(path)),
+		}
+	}
+}
+
+/// The stream of a child process that a line of output was read from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+	Stdout,
+	Stderr,
+}
+
+impl std::fmt::Display for OutputStream {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Stdout => write!(f, "stdout"),
+			Self::Stderr => write!(f, "stderr"),
+		}
+	}
+}
+
+/// Notified of the progress and output of the child processes that a [`ProcessRunner`] or [`ObservedRunner`] spawns
+/// (e.g. to annotate diagnostics for CI or record a run log).
+pub trait ProcessObserver {
+	fn started(&self, _command_line: &str, _current_dir: Option<&Path>) {}
+
+	/// A line of output, and the diagnostic it reported if it has not been seen before in this process.
+	fn output(&self, _stream: OutputStream, _line: &str, _diagnostic: Option<&Diagnostic>) {}
+
+	fn finished(
+		&self,
+		_command_line: &str,
+		_status: &ExitStatus,
+		_diagnostics: &[Diagnostic],
+	) -> anyhow::Result<()> {
+		Ok(())
+	}
+}
+
+pub type SharedProcessObserver = Arc<dyn ProcessObserver + 'static + Send + Sync>;
+
+/// Spawns commands as real child processes.
+#[derive(Default)]
+pub struct ProcessRunner {
+	observers: Vec<SharedProcessObserver>,
+}
+
+impl ProcessRunner {
+	pub fn with_observer(mut self, observer: SharedProcessObserver) -> Self {
+		self.observers.push(observer);
+		self
+	}
+}
+
+impl std::fmt::Debug for ProcessRunner {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ProcessRunner")
+			.field("observers", &self.observers.len())
+			.finish()
+	}
+}
+
+impl CommandRunner for ProcessRunner {
+	fn spawn<'a>(&'a self, command: &'a mut Command) -> PinFutureLifetime<'a, anyhow::Result<()>> {
+		Box::pin(spawn_command(command, self.observers.clone()))
+	}
+}
+
+/// Notifies observers when each command spawned by another runner starts and finishes,
+/// so that a runner which replaces the [`ProcessRunner`] still reports to the run log and annotations.
+///
+/// Observers are not given the output of the commands, as only a [`ProcessRunner`] reads it.
+pub struct ObservedRunner {
+	runner: SharedRunner,
+	observers: Vec<SharedProcessObserver>,
+}
+
+impl ObservedRunner {
+	pub fn new(runner: SharedRunner, observers: Vec<SharedProcessObserver>) -> Self {
+		Self { runner, observers }
+	}
+}
+
+impl std::fmt::Debug for ObservedRunner {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ObservedRunner")
+			.field("observers", &self.observers.len())
+			.finish()
+	}
+}
+
+impl CommandRunner for ObservedRunner {
+	fn spawn<'a>(&'a self, command: &'a mut Command) -> PinFutureLifetime<'a, anyhow::Result<()>> {
+		Box::pin(async move {
+			let record = RecordedCommand::new(command);
+			let command_line = record.command_line();
+			for observer in self.observers.iter() {
+				observer.started(&command_line, record.current_dir.as_deref());
+			}
+
+			let result = self.runner.spawn(command).await;
+			let (status, diagnostics) = match &result {
+				Ok(()) => (exit_status_from_code(0), Vec::new()),
+				Err(err) => match err.downcast_ref::<ProcessFailed>() {
+					Some(failed) => (failed.status, failed.diagnostics.clone()),
+					// The command could not be run, so there is no exit status to report
+					None => return result,
+				},
+			};
+			for observer in self.observers.iter() {
+				observer.finished(&command_line, &status, &diagnostics)?;
+			}
+			result
+		})
+	}
+
+	fn apply(&self, mutation: FileMutation) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.runner.apply(mutation)
+	}
+}
+
+/// Prints every command and filesystem change instead of performing it.
+/// Used by the `--dry-run` flag.
+#[derive(Debug, Default)]
+pub struct DryRunRunner {
+	output: OutputFormat,
+}
+
+impl DryRunRunner {
+	/// A runner which reports each change as text, or as an [`Event::DryRun`] for `--output json`.
+	pub fn new(output: OutputFormat) -> Self {
+		Self { output }
+	}
+
+	fn report(&self, action: String) {
+		match self.output {
+			OutputFormat::Text => println!("[dry-run] Would {action}"),
+			OutputFormat::Json => Event::DryRun { action }.emit(),
+		}
+	}
+}
+
+impl CommandRunner for DryRunRunner {
+	fn spawn<'a>(&'a self, command: &'a mut Command) -> PinFutureLifetime<'a, anyhow::Result<()>> {
+		Box::pin(async move {
+			let record = RecordedCommand::new(command);
+			self.report(format!(
+				"execute \"{}\" in {:?}",
+				record.command_line(),
+				record.current_dir
+			));
+			Ok(())
+		})
+	}
+
+	fn apply(&self, mutation: FileMutation) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		Box::pin(async move {
+			self.report(mutation.to_string());
+			Ok(())
+		})
+	}
+}
+
+/// A command which was provided to a [`RecordingRunner`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedCommand {
+	pub program: String,
+	pub args: Vec<String>,
+	pub current_dir: Option<PathBuf>,
+	/// Environment variables explicitly set (`Some`) or removed (`None`) on the command.
+	pub envs: Vec<(String, Option<String>)>,
+}
+
+impl RecordedCommand {
+	fn new(command: &Command) -> Self {
+		let command = command.as_std();
+		let to_string = |os: &std::ffi::OsStr| os.to_string_lossy().into_owned();
+		Self {
+			program: to_string(command.get_program()),
+			args: command.get_args().map(to_string).collect(),
+			current_dir: command.get_current_dir().map(ToOwned::to_owned),
+			envs: command
+				.get_envs()
+				.map(|(key, value)| (to_string(key), value.map(to_string)))
+				.collect(),
+		}
+	}
+
+	/// The program and its arguments, joined by spaces.
+	pub fn command_line(&self) -> String {
+		std::iter::once(&self.program)
+			.chain(self.args.iter())
+			.cloned()
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+/// The result a [`RecordingRunner`] reports for a command.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedResponse {
+	pub exit_code: i32,
+	pub output: Vec<String>,
+}
+
+impl ScriptedResponse {
+	pub fn success() -> Self {
+		Self::default()
+	}
+
+	pub fn failure(exit_code: i32) -> Self {
+		Self {
+			exit_code,
+			output: Vec::new(),
+		}
+	}
+
+	pub fn with_output<I, S>(mut self, lines: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		self.output = lines.into_iter().map(Into::into).collect();
+		self
+	}
+}
+
+/// Captures every command and filesystem change instead of executing it, replying with scripted responses.
+///
+/// Responses are consumed in the order they were queued.
+/// Once the queue is empty, every command succeeds without output.
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+	commands: Mutex<Vec<RecordedCommand>>,
+	mutations: Mutex<Vec<FileMutation>>,
+	responses: Mutex<VecDeque<ScriptedResponse>>,
+}
+
+impl RecordingRunner {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_response(self, response: ScriptedResponse) -> Self {
+		self.push_response(response);
+		self
+	}
+
+	pub fn push_response(&self, response: ScriptedResponse) {
+		self.responses.lock().unwrap().push_back(response);
+	}
+
+	/// Returns all commands spawned so far, in order.
+	pub fn commands(&self) -> Vec<RecordedCommand> {
+		self.commands.lock().unwrap().clone()
+	}
+
+	/// Returns all filesystem changes requested so far, in order.
+	pub fn mutations(&self) -> Vec<FileMutation> {
+		self.mutations.lock().unwrap().clone()
+	}
+}
+
+impl CommandRunner for RecordingRunner {
+	fn spawn<'a>(&'a self, command: &'a mut Command) -> PinFutureLifetime<'a, anyhow::Result<()>> {
+		Box::pin(async move {
+			let record = RecordedCommand::new(command);
+			self.commands.lock().unwrap().push(record.clone());
+
+			let response = self.responses.lock().unwrap().pop_front();
+			let response = response.unwrap_or_default();
+			if response.exit_code != 0 {
+				let diagnostics = response
+					.output
+					.iter()
+					.filter_map(|line| Diagnostic::parse(line))
+					.collect();
+				return Err(ProcessFailed {
+					program: record.program,
+					args: record.args,
+					status: exit_status_from_code(response.exit_code),
+					tail_of_output: response.output,
+					diagnostics,
+				})?;
+			}
+			Ok(())
+		})
+	}
+
+	fn apply(&self, mutation: FileMutation) -> PinFutureLifetime<'_, anyhow::Result<()>> {
+		self.mutations.lock().unwrap().push(mutation);
+		Box::pin(async { Ok(()) })
+	}
+}
+
+#[cfg(unix)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+	use std::os::unix::process::ExitStatusExt;
+	// The raw wait status stores the exit code in the second byte
+	ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+	use std::os::windows::process::ExitStatusExt;
+	ExitStatus::from_raw(code as u32)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Records the notifications it receives as lines of text.
+	#[derive(Default)]
+	struct RecordingObserver(Mutex<Vec<String>>);
+
+	impl ProcessObserver for RecordingObserver {
+		fn started(&self, command_line: &str, current_dir: Option<&Path>) {
+			let line = format!("started {command_line} in {current_dir:?}");
+			self.0.lock().unwrap().push(line);
+		}
+
+		fn finished(
+			&self,
+			command_line: &str,
+			status: &ExitStatus,
+			diagnostics: &[Diagnostic],
+		) -> anyhow::Result<()> {
+			let line = format!(
+				"finished {command_line} with {:?} ({} diagnostics)",
+				status.code(),
+				diagnostics.len()
+			);
+			self.0.lock().unwrap().push(line);
+			Ok(())
+		}
+	}
+
+	#[tokio::test]
+	async fn observed_runner_reports_the_commands_of_another_runner() {
+		let inner = Arc::new(
+			RecordingRunner::new().with_response(
+				ScriptedResponse::failure(6)
+					.with_output(["Source/Game.cpp(3): error C2065: 'x': undeclared identifier"]),
+			),
+		);
+		let observer = Arc::new(RecordingObserver::default());
+		let runner = ObservedRunner::new(inner.clone(), vec![observer.clone()]);
+
+		let mut command = Command::new("Build.sh");
+		command.arg("GameEditor").current_dir("/ws");
+		let err = runner.spawn(&mut command).await.unwrap_err();
+		assert!(err.downcast_ref::<ProcessFailed>().is_some());
+		runner.spawn(&mut Command::new("true")).await.unwrap();
+		runner
+			.remove_file(PathBuf::from("/ws/Game.modules"))
+			.await
+			.unwrap();
+
+		assert_eq!(
+			*observer.0.lock().unwrap(),
+			[
+				"started Build.sh GameEditor in Some(\"/ws\")",
+				"finished Build.sh GameEditor with Some(6) (1 diagnostics)",
+				"started true in None",
+				"finished true with Some(0) (0 diagnostics)",
+			]
+		);
+		assert_eq!(inner.commands().len(), 2);
+		assert_eq!(
+			inner.mutations(),
+			[FileMutation::RemoveFile(PathBuf::from("/ws/Game.modules"))]
+		);
+	}
+}
@@ -1,27 +1,131 @@
 use anyhow::Context;
+use clap::{Args, FromArgMatches};
+use tracing::Instrument;
 
-use crate::{utility::PinFuture, Config, Package, Plugin};
-use std::sync::{
-	atomic::{self, AtomicBool},
-	Arc,
+use crate::{
+	config::{self, Key, LoadConfigError},
+	diagnostics::{AnnotationFormat, Annotator},
+	logging::{LogOptions, LOG_FILTER_ENV_VAR},
+	output::{Event, JsonEvents, OutputFormat},
+	run_log::RunLog,
+	runner::{DryRunRunner, ObservedRunner, ProcessRunner, SharedProcessObserver, SharedRunner},
+	utility::PinFuture,
+	Config, ExitCode, Package, Plugin, Session,
 };
+use std::{
+	path::PathBuf,
+	sync::{
+		atomic::{self, AtomicBool},
+		Arc,
+	},
+};
+
+/// The subcommands which run even if the config could not be loaded.
+const RUNS_WITHOUT_PROJECT: [&str; 2] = ["doctor", "engines"];
+
+/// The options which apply to every subcommand.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct GlobalArgs {
+	/// Print the commands and file changes that would be made, without executing them.
+	#[arg(long, global = true)]
+	pub dry_run: bool,
+	/// Report as text, or as a stream of JSON events on stdout (one per line) with logs on stderr.
+	#[arg(
+		long,
+		global = true,
+		value_name = "FORMAT",
+		value_enum,
+		default_value_t
+	)]
+	pub output: OutputFormat,
+	/// Report more detail: debug events, or trace events when repeated. Adds timestamps and event targets.
+	#[arg(short, long, global = true, action = clap::ArgAction::Count)]
+	pub verbose: u8,
+	/// Report less: only warnings (including the stderr of child processes), or only errors when repeated.
+	#[arg(short, long, global = true, action = clap::ArgAction::Count)]
+	pub quiet: u8,
+	/// A RUST_LOG-style filter (e.g. `warn,process=info`) which replaces the level from -v/-q. Falls back to RUST_LOG.
+	#[arg(long, global = true, value_name = "FILTER", env = LOG_FILTER_ENV_VAR)]
+	pub log_filter: Option<String>,
+	/// Override a config value for this run (e.g. --set engine-path=/opt/UE_5.3).
+	#[arg(
+		long = "set",
+		global = true,
+		value_name = "KEY=VALUE",
+		value_parser = config::parse_override
+	)]
+	pub overrides: Vec<(Key, String)>,
+	/// Report the warnings and errors in build output for a CI system.
+	#[arg(long, global = true, value_name = "FORMAT", value_enum)]
+	pub annotations: Option<AnnotationFormat>,
+	/// Where `--annotations junit` writes its report.
+	#[arg(
+		long,
+		global = true,
+		value_name = "PATH",
+		default_value = "uebuild-junit.xml"
+	)]
+	pub junit_path: PathBuf,
+	/// The .uproject, or the directory containing it, to use instead of searching the current directory and its parents.
+	#[arg(long, global = true, value_name = "PATH")]
+	pub project: Option<PathBuf>,
+	/// Use the values and command defaults of a profile in the project config.
+	#[arg(long, global = true, value_name = "NAME", env = config::PROFILE_ENV_VAR)]
+	pub profile: Option<String>,
+}
+
+impl GlobalArgs {
+	pub fn log_options(&self) -> LogOptions {
+		LogOptions {
+			verbose: self.verbose,
+			quiet: self.quiet,
+			filter: self.log_filter.clone().or_else(|| {
+				std::env::var("RUST_LOG")
+					.ok()
+					.filter(|filter| !filter.is_empty())
+			}),
+		}
+	}
+
+	pub fn command_line_layer(&self) -> config::CommandLineLayer {
+		config::CommandLineLayer {
+			dry_run: self.dry_run,
+			project: self.project.clone(),
+			profile: self.profile.clone(),
+			values: self.overrides.clone(),
+		}
+	}
+}
 
 pub struct Runtime {
 	#[allow(dead_code)]
 	package: Package,
 	command: Option<clap::Command>,
 	plugins: Vec<Arc<dyn Plugin + 'static + Send + Sync>>,
+	/// The runner to use instead of a [`ProcessRunner`], wrapped in an [`ObservedRunner`] to report to the run log and annotations.
+	runner: Option<SharedRunner>,
 }
 
 impl Runtime {
 	pub fn new(package: Package) -> Self {
 		Self {
-			command: Some(package.new_command()),
+			command: Some(GlobalArgs::augment_args(package.new_command())),
 			package,
 			plugins: Vec::new(),
+			runner: None,
 		}
 	}
 
+	/// Replaces the runner that operations use to execute child processes.
+	///
+	/// The run log, annotations and `--output json` events are still told when each command starts and finishes,
+	/// but not given its output, which only a [`ProcessRunner`] reads.
+	/// `--dry-run` takes precedence over the runner.
+	pub fn with_runner(mut self, runner: SharedRunner) -> Self {
+		self.runner = Some(runner);
+		self
+	}
+
 	pub fn with_plugin(mut self, plugin: impl Plugin + 'static + Send + Sync) -> Self {
 		self.plugins.push(Arc::new(plugin));
 		self
@@ -46,7 +150,13 @@ impl Runtime {
 		self.command = Some(augment(command));
 	}
 
-	pub async fn run(self) -> anyhow::Result<()> {
+	/// Parses the command line and runs the requested operation,
+	/// returning the code that the process should exit with.
+	pub async fn run(mut self) -> ExitCode {
+		let started = std::time::Instant::now();
+		let globals = self.prescan();
+		let output = globals.output;
+		globals.log_options().init(output);
 		let terminate_signal = Arc::new(AtomicBool::new(false));
 		let _ = signal_hook::flag::register(signal_hook::consts::SIGINT, terminate_signal.clone());
 
@@ -54,61 +164,272 @@ impl Runtime {
 			while !terminate_signal.load(atomic::Ordering::Relaxed) {
 				tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 			}
-			println!("Encountered terminate signal, cli task will be aborted");
+			tracing::warn!("Encountered terminate signal, cli task will be aborted");
 		});
 		let cli_handle = tokio::task::spawn(async move {
-			if let Err(err) = self.execute_cli().await {
-				eprintln!("{err:?}");
+			match self.execute_cli(globals).await {
+				Ok(()) => (ExitCode::Success, None),
+				Err(err) => {
+					match err.downcast_ref::<clap::Error>() {
+						Some(usage) => {
+							let _ = usage.print();
+						}
+						None => eprintln!("{err:?}"),
+					}
+					(
+						ExitCode::from_error(&err),
+						Some(err.root_cause().to_string()),
+					)
+				}
 			}
 		});
 
-		futures::future::select(cli_handle, term_handle).await;
+		let (code, error) = match futures::future::select(cli_handle, term_handle).await {
+			futures::future::Either::Left((Ok(result), _)) => result,
+			// The cli task panicked
+			futures::future::Either::Left((Err(_), _)) => {
+				(ExitCode::Failure, Some("panicked".to_owned()))
+			}
+			futures::future::Either::Right(_) => {
+				(ExitCode::Interrupted, Some("interrupted".to_owned()))
+			}
+		};
+		if output == OutputFormat::Json {
+			Event::Result {
+				success: code == ExitCode::Success,
+				exit_code: code.code(),
+				duration_ms: started.elapsed().as_millis(),
+				error,
+			}
+			.emit();
+		}
+		code
+	}
 
-		Ok(())
+	/// Parses the global arguments, which are needed to set up logging and load the config before the full command can be built.
+	/// The subcommands are included (accepting any values, as their value parsers may need the config)
+	/// so that their options and values are not mistaken for global flags. Errors are left for the full parse to report.
+	fn prescan(&mut self) -> GlobalArgs {
+		let base_command = self.command.clone();
+		let placeholder = Session::new(Config::default(), Arc::new(ProcessRunner::default()));
+		self.load_subcommands(&placeholder);
+		let command = std::mem::replace(&mut self.command, base_command).unwrap();
+		prescan_globals(command, std::env::args_os())
 	}
 
-	async fn execute_cli(mut self) -> anyhow::Result<()> {
+	async fn execute_cli(mut self, globals: GlobalArgs) -> anyhow::Result<()> {
 		// Load any .env file that may or may not exist.
 		let _ = dotenv::dotenv();
-
-		let config = Config::load().await?;
-		self.load_subcommands(&config);
+		let output = globals.output;
 
 		// Load the config from disk
-		Config::set_global(config);
+		let command_line = globals.command_line_layer();
+		let load_runner: SharedRunner = match (command_line.dry_run, &self.runner) {
+			(true, _) => Arc::new(DryRunRunner::new(output)),
+			(false, Some(runner)) => runner.clone(),
+			(false, None) => Arc::new(ProcessRunner::default()),
+		};
+		// Help, `doctor` and `engines` are still useful without a loadable project, so the error waits for the command.
+		let (config, load_error) = match Config::load(&command_line, &load_runner).await {
+			Ok(config) => (config, None),
+			Err(err) => (Config::default(), Some(err.context(LoadConfigError))),
+		};
+		let session = Session::new(config, load_runner).with_output(output);
+		// Subcommands are given the session so that value parsers can use the config.
+		self.load_subcommands(&session);
+
 		// Parse the command line args as a cli operation
-		let matches = self.command.take().unwrap().get_matches();
+		let matches = match self.command.take().unwrap().try_get_matches() {
+			Ok(matches) => matches,
+			// Help and version requests are reported as errors by clap, but are printed to stdout as a success
+			Err(err) if !err.use_stderr() => {
+				let _ = err.print();
+				return Ok(());
+			}
+			Err(err) => return Err(err)?,
+		};
+		if let Some(err) = load_error {
+			match RUNS_WITHOUT_PROJECT.contains(&matches.subcommand_name().unwrap_or_default()) {
+				true => tracing::warn!("{err:#}"),
+				false => return Err(err),
+			}
+		}
+		if output == OutputFormat::Json {
+			Event::CommandStarted {
+				command: &subcommand_path(&matches),
+				args: std::env::args().skip(1).collect(),
+			}
+			.emit();
+		}
 
-		// Config is kept globally until after parse so that value parsers can use it.
-		let config = Config::take_global().unwrap();
+		let globals = GlobalArgs::from_arg_matches(&matches)?;
+		let mut run_log = None;
+		let session = match globals.dry_run {
+			// Nothing is executed in a dry run, so there are no processes to observe
+			true => session.with_runner(Arc::new(DryRunRunner::new(output))),
+			false => {
+				let mut observers: Vec<SharedProcessObserver> = Vec::new();
+				if output == OutputFormat::Json {
+					observers.push(Arc::new(JsonEvents));
+				}
+				if let Some(format) = globals.annotations {
+					let annotator = Annotator::new(format, globals.junit_path.clone(), output);
+					observers.push(Arc::new(annotator));
+				}
+				// Browsing the logs should not rotate away the log being looked for
+				let config = session.config();
+				if config.has_project() && matches.subcommand_name() != Some("logs") {
+					match RunLog::create(&config.run_logs_dir(), config.log_retention()) {
+						Ok(log) => {
+							let log = Arc::new(log);
+							observers.push(log.clone());
+							run_log = Some(log);
+						}
+						Err(err) => tracing::warn!("Not writing a run log: {err:#}"),
+					}
+				}
+				let runner: SharedRunner = match &self.runner {
+					Some(runner) => Arc::new(ObservedRunner::new(runner.clone(), observers)),
+					None => Arc::new(
+						observers
+							.into_iter()
+							.fold(ProcessRunner::default(), ProcessRunner::with_observer),
+					),
+				};
+				session.with_runner(runner)
+			}
+		};
 
-		if let Some(future) = self.run_operation(&matches, config) {
+		if let Some(future) = self.run_operation(&matches, &session) {
 			// Construct the error context because `run` takes ownership of `cli`
 			let failed_context = format!("failed to run {matches:?}");
 			// Actually run the desired commmand with the loaded configuration
-			future.await.context(failed_context)?;
+			// At `ERROR` so that it is never filtered out, which would drop the tag from the events within it.
+			let command = subcommand_path(&matches);
+			let span = tracing::error_span!("command", name = %command);
+			let started = std::time::Instant::now();
+			let result = future.instrument(span).await.context(failed_context);
+			if output == OutputFormat::Json {
+				Event::CommandFinished {
+					command: &command,
+					success: result.is_ok(),
+					duration_ms: started.elapsed().as_millis(),
+				}
+				.emit();
+			}
+			if let Some(run_log) = &run_log {
+				run_log.finish(&result);
+			}
+			result?;
 		}
 
 		Ok(())
 	}
 
-	fn load_subcommands(&mut self, config: &Config) {
+	fn load_subcommands(&mut self, session: &Session) {
 		for plugin in self.plugins.clone().into_iter() {
-			plugin.add_subcommands(self, config);
+			plugin.add_subcommands(self, session);
 		}
 	}
 
 	fn run_operation(
 		&self,
 		matches: &clap::ArgMatches,
-		config: Config,
+		session: &Session,
 	) -> Option<PinFuture<anyhow::Result<()>>> {
-		let mut config = Some(config);
 		for plugin in self.plugins.iter() {
-			if let Some(future) = plugin.run(matches, &mut config) {
+			if let Some(future) = plugin.run(matches, session) {
 				return Some(future);
 			}
 		}
 		None
 	}
 }
+
+/// Parses the global arguments from the command line, ignoring any errors.
+/// Falls back to the defaults (and environment variables) if an invalid global value leaves them incomplete.
+fn prescan_globals(
+	command: clap::Command,
+	args: impl IntoIterator<Item = std::ffi::OsString>,
+) -> GlobalArgs {
+	let command = accept_any_values(command).ignore_errors(true);
+	let parse = |command: clap::Command, args: Vec<std::ffi::OsString>| {
+		let matches = command.try_get_matches_from(args).ok()?;
+		GlobalArgs::from_arg_matches(&matches).ok()
+	};
+	let program = std::ffi::OsString::from(command.get_name());
+	parse(command.clone(), args.into_iter().collect())
+		.or_else(|| parse(command, vec![program]))
+		.unwrap_or_default()
+}
+
+/// Replaces the value parsers of the subcommands' arguments with ones that accept any value.
+fn accept_any_values(command: clap::Command) -> clap::Command {
+	command
+		.mut_args(
+			|arg| match arg.is_global_set() || !arg.get_action().takes_values() {
+				true => arg,
+				false => arg.value_parser(clap::builder::ValueParser::os_string()),
+			},
+		)
+		.mut_subcommands(accept_any_values)
+}
+
+/// The names of the subcommands selected on the command line (e.g. `plugin enable`).
+fn subcommand_path(matches: &clap::ArgMatches) -> String {
+	let mut names = Vec::new();
+	let mut matches = matches;
+	while let Some((name, sub_matches)) = matches.subcommand() {
+		names.push(name);
+		matches = sub_matches;
+	}
+	names.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn prescan(args: &[&str]) -> GlobalArgs {
+		let needs_project = |_: &str| Err::<String, _>("requires a project".to_owned());
+		let compile = clap::Command::new("compile")
+			.arg(clap::Arg::new("target").short('t').long("target"))
+			.arg(clap::Arg::new("map").value_parser(needs_project));
+		let command = GlobalArgs::augment_args(clap::Command::new("uebuild")).subcommand(compile);
+		let args = std::iter::once("uebuild").chain(args.iter().copied());
+		prescan_globals(command, args.map(Into::into))
+	}
+
+	#[test]
+	fn finds_global_args_after_the_subcommand() {
+		let globals = prescan(&["-v", "compile", "Map", "-q", "--output=json", "--dry-run"]);
+		assert_eq!(globals.quiet, 1);
+		assert_eq!(globals.output, OutputFormat::Json);
+		assert!(globals.dry_run);
+	}
+
+	#[test]
+	fn values_which_look_like_flags_are_not_global_args() {
+		let globals = prescan(&[
+			"--set",
+			"log-retention=-v",
+			"--profile=-qq",
+			"compile",
+			"--target=--output=json",
+			"--",
+			"-v",
+		]);
+		assert_eq!(globals.verbose, 0);
+		assert_eq!(globals.quiet, 0);
+		assert_eq!(globals.output, OutputFormat::Text);
+		assert_eq!(globals.profile.as_deref(), Some("-qq"));
+		assert_eq!(globals.overrides, [(Key::LogRetention, "-v".to_owned())]);
+	}
+
+	#[test]
+	fn errors_keep_the_defaults() {
+		let globals = prescan(&["--output", "yaml", "compile", "-v"]);
+		assert_eq!(globals.output, OutputFormat::Text);
+		assert_eq!(globals.junit_path, PathBuf::from("uebuild-junit.xml"));
+	}
+}
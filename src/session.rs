@@ -0,0 +1,78 @@
+use crate::{output::OutputFormat, runner::SharedRunner, Config};
+use std::sync::Arc;
+
+/// The loaded configuration and the runner for a single invocation, shared by the runtime, value parsers and operations.
+///
+/// Cloning a session is cheap, and sessions can be freely sent between threads.
+#[derive(Clone)]
+pub struct Session {
+	config: Arc<Config>,
+	runner: SharedRunner,
+	output: OutputFormat,
+}
+
+impl Session {
+	pub fn new(config: Config, runner: SharedRunner) -> Self {
+		Self {
+			config: Arc::new(config),
+			runner,
+			output: OutputFormat::default(),
+		}
+	}
+
+	/// Returns a session with the same configuration which executes commands with a different runner.
+	pub fn with_runner(mut self, runner: SharedRunner) -> Self {
+		self.runner = runner;
+		self
+	}
+
+	/// Returns a session which reports in a different format.
+	pub fn with_output(mut self, output: OutputFormat) -> Self {
+		self.output = output;
+		self
+	}
+
+	pub fn config(&self) -> &Config {
+		&self.config
+	}
+
+	pub fn runner(&self) -> &SharedRunner {
+		&self.runner
+	}
+
+	/// The format selected by `--output`; commands which report or list something
+	/// emit their results with [`emit_data`](crate::output::emit_data) when it is JSON.
+	pub fn output(&self) -> OutputFormat {
+		self.output
+	}
+}
+
+impl std::fmt::Debug for Session {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Session")
+			.field("config", &self.config)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Returns the session's config for a value parser which needs a project,
+/// failing with a clap error if there is no session or it has no project loaded.
+pub(crate) fn require_project<'a>(
+	session: Option<&'a Session>,
+	cmd: &clap::Command,
+	arg: Option<&clap::Arg>,
+) -> Result<&'a Config, clap::Error> {
+	match session.map(Session::config) {
+		Some(config) if config.has_project() => Ok(config),
+		_ => {
+			let arg = arg.map(ToString::to_string).unwrap_or_default();
+			let message = format!(
+				"'{arg}' requires a project, but no .uproject was found. \
+				Run uebuild from within the project directory or pass --project <path>."
+			);
+			Err(cmd
+				.clone()
+				.error(clap::error::ErrorKind::InvalidValue, message))
+		}
+	}
+}
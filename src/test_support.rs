@@ -0,0 +1,22 @@
+//! Fixtures shared by the unit tests.
+
+use crate::{config::Config, runner::RecordingRunner, unreal::UProject, Session};
+use std::{
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+/// A fresh directory under the system temp directory, named by the test (e.g. `compile-fixup`).
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("uebuild-{name}-{}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+/// A session for the project `Game` at `root`, using the engine at `/engine`,
+/// whose commands and file changes are captured by the runner.
+pub(crate) fn session(root: &Path, project: UProject, runner: &Arc<RecordingRunner>) -> Session {
+	let config = Config::for_project(Path::new("/engine"), root, "Game", project);
+	Session::new(config, runner.clone())
+}
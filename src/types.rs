@@ -1,5 +1,7 @@
 mod configuration;
 pub use configuration::*;
+mod host_platform;
+pub use host_platform::*;
 mod platform;
 pub use platform::*;
 mod target;
@@ -0,0 +1,94 @@
+use super::Platform;
+use std::path::PathBuf;
+
+/// The operating system that uebuild, and therefore the engine's tooling, is running on.
+/// Determines which entry points (batch files vs shell scripts) and binaries are used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HostPlatform {
+	Windows,
+	Linux,
+	Mac,
+}
+
+impl HostPlatform {
+	pub fn current() -> Self {
+		if cfg!(target_os = "windows") {
+			Self::Windows
+		} else if cfg!(target_os = "macos") {
+			Self::Mac
+		} else {
+			Self::Linux
+		}
+	}
+
+	/// The target platform which builds for this host (e.g. `Win64` on Windows).
+	pub fn target_platform(&self) -> Platform {
+		match self {
+			Self::Windows => Platform::Windows,
+			Self::Linux => Platform::Linux,
+			Self::Mac => Platform::Mac,
+		}
+	}
+
+	/// Path to the UnrealBuildTool entry point, relative to the engine directory.
+	pub fn build_script(&self) -> PathBuf {
+		PathBuf::from(match self {
+			Self::Windows => "Build/BatchFiles/Build.bat",
+			Self::Linux => "Build/BatchFiles/Linux/Build.sh",
+			Self::Mac => "Build/BatchFiles/Mac/Build.sh",
+		})
+	}
+
+	/// Path to the AutomationTool entry point, relative to the engine directory.
+	pub fn run_uat_script(&self) -> PathBuf {
+		PathBuf::from(match self {
+			Self::Windows => "Build/BatchFiles/RunUAT.bat",
+			Self::Linux | Self::Mac => "Build/BatchFiles/RunUAT.sh",
+		})
+	}
+
+	/// Path to the project file generator, relative to the engine directory.
+	pub fn generate_project_files_script(&self) -> PathBuf {
+		PathBuf::from(match self {
+			Self::Windows => "Build/BatchFiles/GenerateProjectFiles.bat",
+			Self::Linux => "Build/BatchFiles/Linux/GenerateProjectFiles.sh",
+			Self::Mac => "Build/BatchFiles/Mac/GenerateProjectFiles.sh",
+		})
+	}
+
+	/// The directory under `Binaries` that contains executables for this host.
+	pub fn binaries_dir(&self) -> PathBuf {
+		use crate::utility::AsUnrealStr;
+		PathBuf::from("Binaries").join(self.target_platform().as_ue())
+	}
+
+	/// Converts the name of a program into the file name of its executable (e.g. `p4` => `p4.exe`).
+	pub fn executable_name(&self, stem: &str) -> String {
+		match self {
+			Self::Windows => format!("{stem}.exe"),
+			Self::Linux | Self::Mac => stem.to_owned(),
+		}
+	}
+
+	/// Path to an engine executable (e.g. the editor) relative to the [`binaries_dir`](Self::binaries_dir).
+	/// On Mac, executables are nested in an application bundle.
+	pub fn engine_executable(&self, stem: &str) -> PathBuf {
+		match self {
+			Self::Mac => PathBuf::from(format!("{stem}.app/Contents/MacOS/{stem}")),
+			_ => PathBuf::from(self.executable_name(stem)),
+		}
+	}
+
+	/// Where the Epic Games Launcher installs an engine of the provided version, if it supports this host.
+	pub fn default_install_dir(&self, version: &str) -> Option<PathBuf> {
+		match self {
+			Self::Windows => Some(PathBuf::from(format!(
+				"C:\\Program Files\\Epic Games\\UE_{version}\\Engine"
+			))),
+			Self::Mac => Some(PathBuf::from(format!(
+				"/Users/Shared/Epic Games/UE_{version}/Engine"
+			))),
+			Self::Linux => None,
+		}
+	}
+}
@@ -1,13 +1,29 @@
 use crate::utility::AsUnrealStr;
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
 pub enum Platform {
 	Windows,
 	PS4,
 	Switch,
 	XboxOne,
 	Linux,
+	Mac,
+}
+
+impl Platform {
+	/// The name of the platform's config directory and files (e.g. `Config/Windows/WindowsEngine.ini`).
+	pub fn ini_name(&self) -> &'static str {
+		match self {
+			Self::Windows => "Windows",
+			Self::PS4 => "PS4",
+			Self::Switch => "Switch",
+			Self::XboxOne => "XboxOne",
+			Self::Linux => "Linux",
+			Self::Mac => "Mac",
+		}
+	}
 }
 
 impl AsUnrealStr for Platform {
@@ -18,6 +34,7 @@ impl AsUnrealStr for Platform {
 			Self::Switch => "Switch",
 			Self::XboxOne => "XboxOne",
 			Self::Linux => "Linux",
+			Self::Mac => "Mac",
 		}
 	}
 }
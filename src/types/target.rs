@@ -20,3 +20,50 @@ impl AsUnrealStr for Target {
 		}
 	}
 }
+
+impl std::fmt::Display for Target {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_ue())
+	}
+}
+
+/// Selects a target to build, either by one of the [`Target`] shorthands or by the name of any target
+/// declared in the project's `Source/*.Target.cs` files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetSelector {
+	Shorthand(Target),
+	Name(String),
+}
+
+impl std::str::FromStr for TargetSelector {
+	type Err = std::convert::Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match Target::from_str(s, true) {
+			Ok(target) => Self::Shorthand(target),
+			Err(_) => Self::Name(s.to_owned()),
+		})
+	}
+}
+
+impl std::fmt::Display for TargetSelector {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Shorthand(target) => write!(f, "{}", target.as_ue()),
+			Self::Name(name) => write!(f, "{name}"),
+		}
+	}
+}
+
+impl Serialize for TargetSelector {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for TargetSelector {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let name = String::deserialize(deserializer)?;
+		Ok(name.parse().unwrap())
+	}
+}
@@ -1,4 +1,4 @@
-use crate::{utility::PinFuture, Config, Runtime};
+use crate::{utility::PinFuture, Runtime, Session};
 use clap::{FromArgMatches, Parser};
 
 pub mod commands;
@@ -6,23 +6,39 @@ mod editor_modules;
 pub use editor_modules::*;
 mod uproject;
 pub use uproject::*;
+mod uplugin;
+pub use uplugin::*;
 mod editor_target;
 pub use editor_target::*;
+mod engine_registry;
+pub use engine_registry::*;
+mod engine_version;
+pub use engine_version::*;
+pub mod ini;
+mod maps;
+pub use maps::*;
+mod target_rules;
+pub use target_rules::*;
 
 pub struct Unreal;
 impl crate::Plugin for Unreal {
-	fn add_subcommands(&self, runtime: &mut Runtime, _config: &Config) {
+	fn add_subcommands(&self, runtime: &mut Runtime, session: &Session) {
 		runtime.augment_subcommands::<Commands>();
+		runtime.augment_cmd(|cmd| {
+			cmd.mut_subcommand("pisep", |pisep| {
+				commands::RunPisep::bind_session(pisep, session)
+			})
+		});
 	}
 
 	fn run(
 		&self,
 		matches: &clap::ArgMatches,
-		config: &mut Option<Config>,
+		session: &Session,
 	) -> Option<PinFuture<anyhow::Result<()>>> {
 		use crate::Operation;
 		if let Ok(cmds) = Commands::from_arg_matches(matches) {
-			return Some(cmds.run(config.take().unwrap()));
+			return Some(cmds.run(session.clone()));
 		}
 		None
 	}
@@ -38,18 +54,27 @@ pub enum Commands {
 	Pisep(commands::RunPisep),
 	#[command(subcommand)]
 	Loc(commands::localization::Localization),
+	#[command(subcommand)]
+	Engines(commands::engines::Engines),
+	#[command(subcommand)]
+	Plugin(commands::plugins::Plugins),
+	#[command(subcommand)]
+	Ini(commands::ini::Ini),
 }
 
 impl crate::Operation for Commands {
-	fn run(self, config: Config) -> PinFuture<anyhow::Result<()>> {
+	fn run(self, session: Session) -> PinFuture<anyhow::Result<()>> {
 		match self {
-			Self::GenProjectFiles(cmd) => cmd.run(config),
-			Self::FixupBinaries(cmd) => cmd.run(config),
-			Self::Compile(cmd) => cmd.run(config),
-			Self::Cook(cmd) => cmd.run(config),
-			Self::Editor(cmd) => cmd.run(config),
-			Self::Pisep(cmd) => cmd.run(config),
-			Self::Loc(cmd) => cmd.run(config),
+			Self::GenProjectFiles(cmd) => cmd.run(session),
+			Self::FixupBinaries(cmd) => cmd.run(session),
+			Self::Compile(cmd) => cmd.run(session),
+			Self::Cook(cmd) => cmd.run(session),
+			Self::Editor(cmd) => cmd.run(session),
+			Self::Pisep(cmd) => cmd.run(session),
+			Self::Loc(cmd) => cmd.run(session),
+			Self::Engines(cmd) => cmd.run(session),
+			Self::Plugin(cmd) => cmd.run(session),
+			Self::Ini(cmd) => cmd.run(session),
 		}
 	}
 }
@@ -8,4 +8,7 @@ mod run_editor;
 pub use run_editor::*;
 mod run_pisep;
 pub use run_pisep::*;
+pub mod engines;
+pub mod ini;
 pub mod localization;
+pub mod plugins;
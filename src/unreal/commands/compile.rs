@@ -1,8 +1,7 @@
 use crate::{
-	config::Config,
-	types::{Configuration, Platform, Target},
+	types::{Configuration, HostPlatform, Platform, Target, TargetSelector},
 	unreal::BinaryModule,
-	utility::{spawn_command, AsUnrealStr},
+	utility::AsUnrealStr,
 };
 use anyhow::Context;
 use clap::Parser;
@@ -11,27 +10,50 @@ use tokio::process::Command;
 /// Compiles the code for the project.
 #[derive(Parser, Debug)]
 pub struct Compile {
-	#[clap(short, long, value_enum, default_value_t = Target::Editor)]
-	target: Target,
-	#[clap(short, long, value_enum, default_value_t = Platform::Windows)]
-	platform: Platform,
-	#[clap(short, long, value_enum, default_value_t = Configuration::DebugGame)]
-	configuration: Configuration,
+	/// The target to build: one of `editor`, `client` or `server`,
+	/// or the name of any target in the project's `Source/*.Target.cs` files.
+	/// Defaults to the active profile's compile target, or `editor`.
+	#[clap(short, long)]
+	target: Option<TargetSelector>,
+	/// Defaults to the active profile's compile platform, or the host platform.
+	#[clap(short, long, value_enum)]
+	platform: Option<Platform>,
+	/// Defaults to the active profile's compile configuration, or `debug-game`.
+	#[clap(short, long, value_enum)]
+	configuration: Option<Configuration>,
 }
 
 impl crate::Operation for Compile {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			let build_batch = config.engine_path().join("Build/BatchFiles/Build.bat");
-			let project_target_name = config.get_project_target(self.target).unwrap();
-			spawn_command(
-				Command::new(build_batch)
-					.current_dir(config.project_root())
-					.arg(project_target_name)
-					.arg(self.configuration.as_ue())
-					.arg(self.platform.as_ue()),
-			)
-			.await?;
+			let config = session.config();
+			let runner = session.runner();
+			let build_script = config
+				.engine_path()
+				.join(HostPlatform::current().build_script());
+			let defaults = config.active_profile().map(|profile| &profile.compile);
+			let target = self
+				.target
+				.or_else(|| defaults?.target.clone())
+				.unwrap_or(TargetSelector::Shorthand(Target::Editor));
+			let platform = self
+				.platform
+				.or_else(|| defaults?.platform)
+				.unwrap_or(HostPlatform::current().target_platform());
+			let configuration = self
+				.configuration
+				.or_else(|| defaults?.configuration)
+				.unwrap_or(Configuration::DebugGame);
+			let project_target_name = config.resolve_target(&target)?;
+			runner
+				.spawn(
+					Command::new(build_script)
+						.current_dir(config.project_root())
+						.arg(project_target_name)
+						.arg(configuration.as_ue())
+						.arg(platform.as_ue()),
+				)
+				.await?;
 			Ok(())
 		})
 	}
@@ -41,10 +63,17 @@ impl crate::Operation for Compile {
 #[derive(Parser, Debug)]
 pub struct FixupBinaries;
 impl crate::Operation for FixupBinaries {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
 			let project_root = config.project_root();
-			let module_paths = config.project().get_module_paths(Platform::Windows);
+			let host = HostPlatform::current();
+			let module_paths = config.project().get_module_paths(
+				host.target_platform(),
+				config.editor_name(),
+				config.plugins(),
+			);
 
 			let mut files_to_checkout = Vec::new();
 			let mut modules_to_update = Vec::new();
@@ -55,7 +84,7 @@ impl crate::Operation for FixupBinaries {
 					continue;
 				}
 				let mut module = BinaryModule::read(&module_path).await?;
-				let binary_redirectors = module.dealias();
+				let binary_redirectors = module.dealias(config.editor_name());
 				if !binary_redirectors.is_empty() {
 					files_to_checkout.push(module_path.clone());
 					let mut binaries = Vec::with_capacity(binary_redirectors.len());
@@ -70,49 +99,177 @@ impl crate::Operation for FixupBinaries {
 			if !files_to_checkout.is_empty() {
 				let files_to_checkout = files_to_checkout
 					.into_iter()
-					.map(|path| format!("{}", path.display()).replace("/", "\\"))
+					.map(|path| match host {
+						HostPlatform::Windows => format!("{}", path.display()).replace('/', "\\"),
+						_ => format!("{}", path.display()),
+					})
 					.collect::<Vec<_>>();
-				spawn_command(
-					Command::new("p4.exe")
-						.current_dir(project_root)
-						.arg("edit")
-						.args(&files_to_checkout),
-				)
-				.await?;
+				runner
+					.spawn(
+						Command::new(host.executable_name("p4"))
+							.current_dir(project_root)
+							.arg("edit")
+							.args(&files_to_checkout),
+					)
+					.await?;
 			}
 
 			for (module_path, module, binaries_to_move) in modules_to_update.into_iter() {
 				for (prev, next) in binaries_to_move.into_iter() {
 					if prev != next {
-						spawn_command(
-							Command::new("chmod")
-								.arg("777")
-								.arg(format!("{}", prev.display())),
-						)
-						.await?;
-
-						spawn_command(
-							Command::new("chmod")
-								.arg("777")
-								.arg(format!("{}", next.display())),
-						)
-						.await?;
-
-						println!("Moving {prev:?} to {next:?}");
-						tokio::fs::copy(&prev, &next)
+						runner
+							.spawn(
+								Command::new("chmod")
+									.arg("777")
+									.arg(format!("{}", prev.display())),
+							)
+							.await?;
+
+						runner
+							.spawn(
+								Command::new("chmod")
+									.arg("777")
+									.arg(format!("{}", next.display())),
+							)
+							.await?;
+
+						tracing::info!("Moving {prev:?} to {next:?}");
+						runner
+							.copy_file(prev.clone(), next)
 							.await
 							.context("failed to copy file contents")?;
-						tokio::fs::remove_file(&prev)
+						runner
+							.remove_file(prev)
 							.await
 							.context("failed to remove source file")?;
 					}
 				}
 
-				println!("Writing updates to {module_path:?}");
-				module.write(&module_path).await?;
+				tracing::info!("Writing updates to {module_path:?}");
+				module.write(&module_path, runner).await?;
 			}
 
 			Ok(())
 		})
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		runner::{FileMutation, RecordingRunner},
+		test_support::{session, temp_dir},
+		unreal::{Module, UProject},
+		Operation,
+	};
+	use std::{
+		path::{Path, PathBuf},
+		sync::Arc,
+	};
+
+	#[tokio::test]
+	async fn compile_runs_build_script_for_target() {
+		let runner = Arc::new(RecordingRunner::new());
+		let root = PathBuf::from("/projects/Game");
+		let compile = Compile {
+			target: None,
+			platform: Some(Platform::Linux),
+			configuration: Some(Configuration::Shipping),
+		};
+		compile
+			.run(session(&root, UProject::default(), &runner))
+			.await
+			.unwrap();
+
+		let commands = runner.commands();
+		assert_eq!(commands.len(), 1);
+		let expected_program = Path::new("/engine").join(HostPlatform::current().build_script());
+		assert_eq!(commands[0].program, expected_program.to_string_lossy());
+		assert_eq!(commands[0].args, ["GameEditor", "Shipping", "Linux"]);
+		assert_eq!(commands[0].current_dir, Some(root));
+	}
+
+	#[tokio::test]
+	async fn compile_unknown_target_runs_nothing() {
+		let runner = Arc::new(RecordingRunner::new());
+		let compile = Compile {
+			target: Some(TargetSelector::Name("Missing".to_owned())),
+			platform: None,
+			configuration: None,
+		};
+		let result = compile
+			.run(session(
+				Path::new("/projects/Game"),
+				UProject::default(),
+				&runner,
+			))
+			.await;
+		assert!(result.is_err());
+		assert!(runner.commands().is_empty());
+	}
+
+	#[tokio::test]
+	async fn fixup_binaries_renames_aliased_modules() {
+		let root = temp_dir("compile-fixup");
+		let host = HostPlatform::current();
+		let binaries_dir = root.join(format!("Binaries/{}", host.target_platform().as_ue()));
+		std::fs::create_dir_all(&binaries_dir).unwrap();
+		let module_path = binaries_dir.join("UE4Editor.modules");
+		std::fs::write(
+			&module_path,
+			r#"{ "BuildId": "1", "Modules": { "Game": "UE4Editor-Game-0001.dll" } }"#,
+		)
+		.unwrap();
+		let mut project = UProject::default();
+		project.modules.push(Module {
+			name: "Game".to_owned(),
+			..Default::default()
+		});
+
+		let runner = Arc::new(RecordingRunner::new());
+		FixupBinaries
+			.run(session(&root, project, &runner))
+			.await
+			.unwrap();
+
+		let commands = runner.commands();
+		let programs = commands
+			.iter()
+			.map(|command| command.program.as_str())
+			.collect::<Vec<_>>();
+		let p4 = host.executable_name("p4");
+		assert_eq!(programs, [p4.as_str(), "chmod", "chmod", "chmod", "chmod"]);
+		assert_eq!(commands[0].current_dir, Some(root.clone()));
+		assert_eq!(commands[0].args.len(), 4);
+		assert_eq!(commands[0].args[0], "edit");
+		let path = |name: &str| binaries_dir.join(name).to_string_lossy().into_owned();
+		assert_eq!(commands[1].args, ["777", &path("UE4Editor-Game-0001.dll")]);
+		assert_eq!(commands[2].args, ["777", &path("UE4Editor-Game.dll")]);
+		assert_eq!(commands[3].args, ["777", &path("UE4Editor-Game-0001.pdb")]);
+		assert_eq!(commands[4].args, ["777", &path("UE4Editor-Game.pdb")]);
+
+		let mutations = runner.mutations();
+		assert_eq!(mutations.len(), 5);
+		assert_eq!(
+			mutations[0],
+			FileMutation::Copy {
+				from: binaries_dir.join("UE4Editor-Game-0001.dll"),
+				to: binaries_dir.join("UE4Editor-Game.dll"),
+			}
+		);
+		assert_eq!(
+			mutations[1],
+			FileMutation::RemoveFile(binaries_dir.join("UE4Editor-Game-0001.dll"))
+		);
+		let FileMutation::Write { path, contents } = &mutations[4] else {
+			panic!(
+				"expected the modules file to be written, got {:?}",
+				mutations[4]
+			);
+		};
+		assert_eq!(*path, module_path);
+		assert!(String::from_utf8_lossy(contents).contains("\"UE4Editor-Game.dll\""));
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+}
@@ -1,7 +1,6 @@
 use crate::{
-	config::Config,
-	types::{Configuration, Platform, Target},
-	utility::{spawn_command, AsUnrealStr},
+	types::{Configuration, HostPlatform, Platform, Target, TargetSelector},
+	utility::AsUnrealStr,
 };
 use clap::Parser;
 use tokio::process::Command;
@@ -9,58 +8,78 @@ use tokio::process::Command;
 /// Cooks the project to run standalone.
 #[derive(Parser, Debug)]
 pub struct Cook {
-	#[clap(short, long, value_enum, default_value_t = Target::Client)]
-	target: Target,
-	#[clap(short, long, value_enum, default_value_t = Platform::Windows)]
-	platform: Platform,
-	#[clap(short, long, value_enum, default_value_t = Configuration::Development)]
-	configuration: Configuration,
+	/// Defaults to the active profile's cook target, or `client`.
+	#[clap(short, long, value_enum)]
+	target: Option<Target>,
+	/// Defaults to the active profile's cook platform, or the host platform.
+	#[clap(short, long, value_enum)]
+	platform: Option<Platform>,
+	/// Defaults to the active profile's cook configuration, or `development`.
+	#[clap(short, long, value_enum)]
+	configuration: Option<Configuration>,
 	/// Relative path in the project root to output the cooked build to.
 	#[clap(short, long, default_value = "DeploymentBuilds")]
 	dest: String,
 }
 
 impl crate::Operation for Cook {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			let uat_batch = config.engine_path().join("Build/BatchFiles/RunUAT.bat");
+			let config = session.config();
+			let runner = session.runner();
+			let defaults = config.active_profile().map(|profile| &profile.cook);
+			let target = self
+				.target
+				.or_else(|| defaults?.target)
+				.unwrap_or(Target::Client);
+			let platform = self
+				.platform
+				.or_else(|| defaults?.platform)
+				.unwrap_or(HostPlatform::current().target_platform());
+			let configuration = self
+				.configuration
+				.or_else(|| defaults?.configuration)
+				.unwrap_or(Configuration::Development);
+			let uat_script = config
+				.engine_path()
+				.join(HostPlatform::current().run_uat_script());
 			let deploy_dir = config.project_root().join(self.dest);
 			let uproject = config.uproject_path();
-			let project_target_name = config.get_project_target(self.target).unwrap();
-			let mut cmd = Command::new(uat_batch);
+			let project_target_name = config.resolve_target(&TargetSelector::Shorthand(target))?;
+			let mut cmd = Command::new(uat_script);
 			cmd.current_dir(config.project_root())
 				.arg(format!("-ScriptsForProject=\"{}\"", uproject.display()))
 				.arg("BuildCookRun")
 				.arg(format!("-project=\"{}\"", uproject.display()))
 				.arg(format!("-target={}", project_target_name))
-				.args(&["-installed", "-nop4"])
-				.args(&["-build", "-cook", "-stage"])
+				.args(["-installed", "-nop4"])
+				.args(["-build", "-cook", "-stage"])
 				.arg("-archive")
 				.arg(format!("-archivedirectory=\"{}\"", deploy_dir.display()))
 				.arg("-ddc=InstalledDerivedDataBackendGraph")
-				.args(&["-pak", "-prereqs", "-nodebuginfo", "-utf8output"]);
-			match self.target {
+				.args(["-pak", "-prereqs", "-nodebuginfo", "-utf8output"]);
+			match target {
 				Target::Client => {
-					cmd.arg(format!("-targetplatform={}", self.platform.as_ue()));
-					cmd.arg(format!("-clientconfig={}", self.configuration.as_ue()));
+					cmd.arg(format!("-targetplatform={}", platform.as_ue()));
+					cmd.arg(format!("-clientconfig={}", configuration.as_ue()));
 				}
 				Target::Server => {
-					cmd.args(&["-server", "-noclient"]);
-					cmd.arg(format!("-serverplatform={}", self.platform.as_ue()));
-					cmd.arg(format!("-platform={}", self.platform.as_ue()));
-					cmd.arg(format!("-serverconfig={}", self.configuration.as_ue()));
+					cmd.args(["-server", "-noclient"]);
+					cmd.arg(format!("-serverplatform={}", platform.as_ue()));
+					cmd.arg(format!("-platform={}", platform.as_ue()));
+					cmd.arg(format!("-serverconfig={}", configuration.as_ue()));
 					cmd.arg(format!(
 						"-Target=\"{} {} {}\"",
 						project_target_name,
-						self.platform.as_ue(),
-						self.configuration.as_ue()
+						platform.as_ue(),
+						configuration.as_ue()
 					));
 				}
 				Target::Editor => {
 					return Err(InvalidCookTarget)?;
 				}
 			}
-			spawn_command(&mut cmd).await?;
+			runner.spawn(&mut cmd).await?;
 			Ok(())
 		})
 	}
@@ -77,3 +96,94 @@ impl std::fmt::Display for InvalidCookTarget {
 		)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		config::{Config, UnknownTarget},
+		runner::RecordingRunner,
+		test_support::session,
+		unreal::UProject,
+		Operation, Session,
+	};
+	use std::{
+		path::{Path, PathBuf},
+		sync::Arc,
+	};
+
+	fn cook(target: Target) -> Cook {
+		Cook {
+			target: Some(target),
+			platform: Some(Platform::Linux),
+			configuration: Some(Configuration::Shipping),
+			dest: "Builds".to_owned(),
+		}
+	}
+
+	async fn run(cook: Cook) -> (anyhow::Result<()>, Arc<RecordingRunner>) {
+		let runner = Arc::new(RecordingRunner::new());
+		let root = Path::new("/projects/Game");
+		let result = cook.run(session(root, UProject::default(), &runner)).await;
+		(result, runner)
+	}
+
+	#[tokio::test]
+	async fn cooks_client() {
+		let (result, runner) = run(cook(Target::Client)).await;
+		result.unwrap();
+		let commands = runner.commands();
+		assert_eq!(commands.len(), 1);
+		let expected_program = Path::new("/engine").join(HostPlatform::current().run_uat_script());
+		assert_eq!(commands[0].program, expected_program.to_string_lossy());
+		assert_eq!(
+			commands[0].current_dir,
+			Some(PathBuf::from("/projects/Game"))
+		);
+		let args = &commands[0].args;
+		assert_eq!(args[1], "BuildCookRun");
+		assert!(args.contains(&"-project=\"/projects/Game/Game.uproject\"".to_owned()));
+		assert!(args.contains(&"-target=Game".to_owned()));
+		assert!(args.contains(&"-archivedirectory=\"/projects/Game/Builds\"".to_owned()));
+		assert!(args.ends_with(&[
+			"-targetplatform=Linux".to_owned(),
+			"-clientconfig=Shipping".to_owned(),
+		]));
+	}
+
+	#[tokio::test]
+	async fn editor_target_is_an_error() {
+		let (result, runner) = run(cook(Target::Editor)).await;
+		assert!(result.unwrap_err().is::<InvalidCookTarget>());
+		assert!(runner.commands().is_empty());
+	}
+
+	#[tokio::test]
+	async fn missing_target_is_an_error() {
+		let runner = Arc::new(RecordingRunner::new());
+		let config = Config::default();
+		let result = cook(Target::Server)
+			.run(Session::new(config, runner.clone()))
+			.await;
+		assert!(result.unwrap_err().is::<UnknownTarget>());
+		assert!(runner.commands().is_empty());
+	}
+
+	#[tokio::test]
+	async fn cooks_server() {
+		let (result, runner) = run(cook(Target::Server)).await;
+		result.unwrap();
+		let commands = runner.commands();
+		assert_eq!(commands.len(), 1);
+		let args = &commands[0].args;
+		assert!(args.contains(&"-target=GameServer".to_owned()));
+		assert!(args.ends_with(&[
+			"-server".to_owned(),
+			"-noclient".to_owned(),
+			"-serverplatform=Linux".to_owned(),
+			"-platform=Linux".to_owned(),
+			"-serverconfig=Shipping".to_owned(),
+			"-Target=\"GameServer Linux Shipping\"".to_owned(),
+		]));
+	}
+}
@@ -0,0 +1,74 @@
+use crate::{
+	output::{emit_data, OutputFormat},
+	unreal::EngineRegistry,
+};
+use clap::{Parser, Subcommand};
+use serde_json::json;
+
+/// Subcommands to inspect the engines installed on this machine.
+#[derive(Subcommand, Debug)]
+pub enum Engines {
+	List(List),
+}
+
+impl crate::Operation for Engines {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		match self {
+			Self::List(cmd) => cmd.run(session),
+		}
+	}
+}
+
+/// Lists every engine found via UE_ENGINE_ROOT, the launcher, and the Install.ini registry.
+#[derive(Parser, Debug)]
+pub struct List;
+
+impl crate::Operation for List {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let registry = EngineRegistry::discover().await;
+			let association = config.project().get_engine_association();
+			let resolved = registry.resolve(association, config.project_root());
+			if session.output() == OutputFormat::Json {
+				let engines = registry
+					.installs()
+					.iter()
+					.map(|install| {
+						json!({
+							"association": install.association,
+							"source": install.source.to_string(),
+							"engine_dir": install.engine_dir(),
+						})
+					})
+					.collect::<Vec<_>>();
+				return emit_data(&json!({
+					"engines": engines,
+					"project": {
+						"association": association,
+						"resolved": resolved,
+						"configured": config.engine_path(),
+					},
+				}));
+			}
+			println!("Engines:");
+			if registry.installs().is_empty() {
+				println!("  (none found)");
+			}
+			for install in registry.installs().iter() {
+				println!(
+					"  {} ({}) => {:?}",
+					install.association,
+					install.source,
+					install.engine_dir()
+				);
+			}
+
+			println!("Project:");
+			println!("  Association: {association:?}");
+			println!("  Resolved: {resolved:?}");
+			println!("  Configured: {:?}", config.engine_path());
+			Ok(())
+		})
+	}
+}
@@ -0,0 +1,253 @@
+use crate::{
+	config::Config,
+	output::{emit_data, OutputFormat},
+	runner::SharedRunner,
+	types::{HostPlatform, Platform},
+	unreal::ini::{IniEntry, IniFile, IniHierarchy, IniOp},
+};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Subcommands to read and edit the project's config (`.ini`) files.
+#[derive(Subcommand, Debug)]
+pub enum Ini {
+	Get(Get),
+	Set(Set),
+	Unset(Unset),
+	AddArray(AddArray),
+	RemoveArray(RemoveArray),
+}
+
+impl crate::Operation for Ini {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		match self {
+			Self::Get(cmd) => cmd.run(session),
+			Self::Set(cmd) => cmd.run(session),
+			Self::Unset(cmd) => cmd.run(session),
+			Self::AddArray(cmd) => cmd.run(session),
+			Self::RemoveArray(cmd) => cmd.run(session),
+		}
+	}
+}
+
+/// Identifies a key in a config category.
+#[derive(Args, Debug)]
+pub struct IniKey {
+	/// The config category (e.g. `Engine` or `Game`).
+	category: String,
+	/// The section containing the key (e.g. `/Script/EngineSettings.GameMapsSettings`).
+	section: String,
+	key: String,
+	/// Use the platform's `Config/<Platform>/<Platform><Category>.ini` instead of `Config/Default<Category>.ini`.
+	#[clap(short, long, value_enum)]
+	platform: Option<Platform>,
+}
+
+impl IniKey {
+	fn project_file(&self, config: &Config) -> PathBuf {
+		config
+			.ini_locations()
+			.project_file(&self.category, self.platform)
+	}
+
+	async fn read_project_file(&self, config: &Config) -> anyhow::Result<(PathBuf, IniFile)> {
+		let path = self.project_file(config);
+		let file = match path.exists() {
+			true => IniFile::read(&path).await?,
+			false => IniFile::default(),
+		};
+		Ok((path, file))
+	}
+}
+
+/// Writes the file if it was changed, creating its directory if needed.
+async fn write_if_changed(
+	path: PathBuf,
+	file: &IniFile,
+	changed: bool,
+	runner: &SharedRunner,
+) -> anyhow::Result<()> {
+	if !changed {
+		tracing::info!("No changes to {}", path.display());
+		return Ok(());
+	}
+	if let Some(dir) = path.parent() {
+		if !dir.exists() {
+			runner.create_dir_all(dir.to_owned()).await?;
+		}
+	}
+	tracing::info!("Updating {}", path.display());
+	runner
+		.write_file(path, file.to_string().into_bytes())
+		.await?;
+	Ok(())
+}
+
+/// Prints the value of a key as the engine sees it, after all config layers are merged.
+///
+/// Array keys print one value per line.
+#[derive(Parser, Debug)]
+pub struct Get {
+	#[clap(flatten)]
+	key: IniKey,
+}
+
+impl crate::Operation for Get {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let IniKey {
+				category,
+				section,
+				key,
+				platform,
+			} = &self.key;
+			let platform = platform.unwrap_or(HostPlatform::current().target_platform());
+			let ini = config.load_ini(category, Some(platform)).await?;
+			let values = ini.get_all(section, key);
+			if values.is_empty() {
+				return Err(MissingIniValue {
+					section: section.clone(),
+					key: key.clone(),
+				})?;
+			}
+			if session.output() == OutputFormat::Json {
+				return emit_data(&values);
+			}
+			for value in values.into_iter() {
+				println!("{value}");
+			}
+			Ok(())
+		})
+	}
+}
+
+/// Sets the key to a single value in the project's config file.
+#[derive(Parser, Debug)]
+pub struct Set {
+	#[clap(flatten)]
+	key: IniKey,
+	value: String,
+}
+
+impl crate::Operation for Set {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
+			let (path, mut file) = self.key.read_project_file(config).await?;
+			let changed = file.get(&self.key.section, &self.key.key) != Some(self.value.as_str());
+			file.set(&self.key.section, &self.key.key, &self.value);
+			write_if_changed(path, &file, changed, runner).await
+		})
+	}
+}
+
+/// Removes every entry for the key from the project's config file.
+///
+/// Values from the engine's config files or other project layers still apply.
+#[derive(Parser, Debug)]
+pub struct Unset {
+	#[clap(flatten)]
+	key: IniKey,
+}
+
+impl crate::Operation for Unset {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
+			let (path, mut file) = self.key.read_project_file(config).await?;
+			let changed = file.unset(&self.key.section, &self.key.key);
+			write_if_changed(path, &file, changed, runner).await
+		})
+	}
+}
+
+/// Adds a value to an array key (`+Key=Value`) in the project's config file.
+#[derive(Parser, Debug)]
+pub struct AddArray {
+	#[clap(flatten)]
+	key: IniKey,
+	value: String,
+}
+
+impl crate::Operation for AddArray {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
+			let (path, mut file) = self.key.read_project_file(config).await?;
+			let changed = file.add_to_array(&self.key.section, &self.key.key, &self.value);
+			write_if_changed(path, &file, changed, runner).await
+		})
+	}
+}
+
+/// Removes a value from an array key in the project's config file.
+///
+/// Entries in the file which add the value are dropped.
+/// If the value is still inherited from a lower layer (e.g. the engine's `Base*.ini`), a `-Key=Value` entry is added.
+#[derive(Parser, Debug)]
+pub struct RemoveArray {
+	#[clap(flatten)]
+	key: IniKey,
+	value: String,
+}
+
+impl crate::Operation for RemoveArray {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
+			let IniKey {
+				category,
+				section,
+				key,
+				platform,
+			} = &self.key;
+			let (path, mut file) = self.key.read_project_file(config).await?;
+			let mut changed = file.remove_array_additions(section, key, &self.value);
+
+			// Merge the layers up to and including the edited file to see if the value is inherited
+			let mut ini = IniHierarchy::default();
+			for layer in config.ini_locations().layers(category, *platform) {
+				if layer == path {
+					ini.apply(&file);
+					break;
+				}
+				if layer.exists() {
+					ini.apply(&IniFile::read(&layer).await?);
+				}
+			}
+			// Each removal only drops one inherited copy of the value
+			let inherited = ini
+				.get_all(section, key)
+				.into_iter()
+				.filter(|value| *value == self.value)
+				.count();
+			for _ in 0..inherited {
+				let entry = IniEntry {
+					op: IniOp::Remove,
+					key: key.clone(),
+					value: self.value.clone(),
+				};
+				file.push_entry(section, entry);
+				changed = true;
+			}
+
+			write_if_changed(path, &file, changed, runner).await
+		})
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct MissingIniValue {
+	pub section: String,
+	pub key: String,
+}
+impl std::fmt::Display for MissingIniValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "No value for {} in [{}]", self.key, self.section)
+	}
+}
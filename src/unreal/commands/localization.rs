@@ -4,11 +4,11 @@ use std::{
 	str::FromStr,
 };
 
+use crate::unreal::ini::IniFile;
 use anyhow::Context;
 use clap::{Parser, Subcommand};
 use tokio::process::Command;
-
-use crate::utility::spawn_command;
+use tracing::Instrument;
 
 /// Subcommands to handle localization files.
 #[derive(Subcommand, Debug)]
@@ -23,31 +23,27 @@ pub enum Localization {
 }
 
 impl crate::Operation for Localization {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		match self {
-			Self::Gather(cmd) => cmd.run(config),
-			Self::Export(cmd) => cmd.run(config),
-			Self::Compile(cmd) => cmd.run(config),
-			Self::Import(cmd) => cmd.run(config),
-			Self::Update(cmd) => cmd.run(config),
-			Self::ExportZip(cmd) => cmd.run(config),
-			Self::ImportZip(cmd) => cmd.run(config),
+			Self::Gather(cmd) => cmd.run(session),
+			Self::Export(cmd) => cmd.run(session),
+			Self::Compile(cmd) => cmd.run(session),
+			Self::Import(cmd) => cmd.run(session),
+			Self::Update(cmd) => cmd.run(session),
+			Self::ExportZip(cmd) => cmd.run(session),
+			Self::ImportZip(cmd) => cmd.run(session),
 		}
 	}
 }
 
 impl Localization {
-	fn make_command(
-		config: &crate::config::Config,
-		loc_config: &PathBuf,
-	) -> anyhow::Result<Command> {
+	fn make_command(config: &crate::config::Config, loc_config: &Path) -> anyhow::Result<Command> {
 		let mut cmd = Command::new(config.editor_binary());
 		cmd.current_dir(config.project_root());
 		cmd.arg(config.uproject_path());
 		cmd.arg("-run=GatherText");
 		cmd.arg(format!("-config={}", loc_config.to_str().unwrap()));
-		cmd.arg("-EnableSCC -DisableSCCSubmit");
-		cmd.arg("-DisableSCCSubmit");
+		cmd.args(["-EnableSCC", "-DisableSCCSubmit"]);
 		Ok(cmd)
 	}
 
@@ -62,6 +58,7 @@ impl Localization {
 
 	async fn make_loc_config(
 		config: &crate::config::Config,
+		runner: &crate::runner::SharedRunner,
 		base_name: &str,
 		lang: Option<String>,
 	) -> anyhow::Result<(PathBuf, Option<PathBuf>)> {
@@ -99,9 +96,10 @@ impl Localization {
 		let mono_lang_content = mono_lang_content.join("\n");
 
 		let mono_lang_name = format!("{base_name}_{lang}.ini");
-		let path_abs = Self::make_cfg_ini_path(Some(&config.project_root()), &mono_lang_name);
+		let path_abs = Self::make_cfg_ini_path(Some(config.project_root()), &mono_lang_name);
 		let path_rel = Self::make_cfg_ini_path(None, &mono_lang_name);
-		tokio::fs::write(&path_abs, mono_lang_content)
+		runner
+			.write_file(path_abs.clone(), mono_lang_content.into_bytes())
 			.await
 			.context(format!("write temporary {:?}", path_abs))?;
 
@@ -110,18 +108,20 @@ impl Localization {
 
 	async fn run_command(
 		config: &crate::config::Config,
+		runner: &crate::runner::SharedRunner,
 		base_name: &str,
 		lang: Option<String>,
 	) -> anyhow::Result<()> {
-		let (loc_config, temporary_path) = Self::make_loc_config(&config, base_name, lang)
+		let (loc_config, temporary_path) = Self::make_loc_config(config, runner, base_name, lang)
 			.await
 			.context("make temporary config")?;
 
-		let mut cmd = Self::make_command(&config, &loc_config).context("make command")?;
-		spawn_command(&mut cmd).await.context("run command")?;
+		let mut cmd = Self::make_command(config, &loc_config).context("make command")?;
+		runner.spawn(&mut cmd).await.context("run command")?;
 
 		if let Some(temp) = temporary_path {
-			tokio::fs::remove_file(temp)
+			runner
+				.remove_file(temp)
 				.await
 				.context("remove temporary config")?;
 		}
@@ -138,9 +138,11 @@ pub struct Gather {
 }
 
 impl crate::Operation for Gather {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			Localization::run_command(&config, "Game_Gather", self.lang)
+			let config = session.config();
+			let runner = session.runner();
+			Localization::run_command(config, runner, "Game_Gather", self.lang)
 				.await
 				.context("gather localization")?;
 			Ok(())
@@ -156,9 +158,11 @@ pub struct Export {
 }
 
 impl crate::Operation for Export {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			Localization::run_command(&config, "Game_Export", self.lang)
+			let config = session.config();
+			let runner = session.runner();
+			Localization::run_command(config, runner, "Game_Export", self.lang)
 				.await
 				.context("export localization")?;
 			Ok(())
@@ -174,9 +178,11 @@ pub struct Compile {
 }
 
 impl crate::Operation for Compile {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			Localization::run_command(&config, "Game_Compile", self.lang)
+			let config = session.config();
+			let runner = session.runner();
+			Localization::run_command(config, runner, "Game_Compile", self.lang)
 				.await
 				.context("compile localization")?;
 			Ok(())
@@ -194,19 +200,23 @@ pub struct Import {
 impl Import {
 	async fn get_source_path(config: &crate::config::Config) -> anyhow::Result<PathBuf> {
 		let import_ini_path =
-			Localization::make_cfg_ini_path(Some(&config.project_root()), "Game_Import.ini");
-		let content = tokio::fs::read_to_string(&import_ini_path)
+			Localization::make_cfg_ini_path(Some(config.project_root()), "Game_Import.ini");
+		let content = IniFile::read(&import_ini_path)
 			.await
 			.context("read Game_Import.ini")?;
-		let entry = crate::config::read_ini_prop(&content, "SourcePath");
-		Ok(PathBuf::from_str(entry.unwrap()).unwrap())
+		let entry = content
+			.get("CommonSettings", "SourcePath")
+			.context("Game_Import.ini does not set [CommonSettings] SourcePath")?;
+		Ok(PathBuf::from(entry))
 	}
 }
 
 impl crate::Operation for Import {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			Localization::run_command(&config, "Game_Import", self.lang)
+			let config = session.config();
+			let runner = session.runner();
+			Localization::run_command(config, runner, "Game_Import", self.lang)
 				.await
 				.context("import localization")?;
 			Ok(())
@@ -222,9 +232,9 @@ pub struct Update {
 }
 
 impl crate::Operation for Update {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			println!("Updating localization files...\n");
+			tracing::info!("Updating localization files");
 			let gather = Gather {
 				lang: self.lang.clone(),
 			};
@@ -234,14 +244,26 @@ impl crate::Operation for Update {
 			let compile = Compile {
 				lang: self.lang.clone(),
 			};
-			println!("Gather:");
-			gather.run(config.clone()).await.context("gather")?;
-			println!("Export:");
-			export.run(config.clone()).await.context("export")?;
-			println!("Compile:");
-			compile.run(config.clone()).await.context("compile")?;
-			println!("Zip PO Files:");
-			ExportPOZip.run(config).await.context("zip")?;
+			gather
+				.run(session.clone())
+				.instrument(tracing::error_span!("step", name = "gather"))
+				.await
+				.context("gather")?;
+			export
+				.run(session.clone())
+				.instrument(tracing::error_span!("step", name = "export"))
+				.await
+				.context("export")?;
+			compile
+				.run(session.clone())
+				.instrument(tracing::error_span!("step", name = "compile"))
+				.await
+				.context("compile")?;
+			ExportPOZip
+				.run(session)
+				.instrument(tracing::error_span!("step", name = "zip"))
+				.await
+				.context("zip")?;
 			Ok(())
 		})
 	}
@@ -252,8 +274,10 @@ impl crate::Operation for Update {
 pub struct ExportPOZip;
 
 impl crate::Operation for ExportPOZip {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
 			let loc_root = config.project_root().join("Content/Localization/Game");
 			let mut lang_paths = Vec::new();
 			for entry in std::fs::read_dir(&loc_root)? {
@@ -269,13 +293,8 @@ impl crate::Operation for ExportPOZip {
 				.project_root()
 				.join("Content/Localization")
 				.join(archive_name);
-			let out_file = std::fs::File::options()
-				.create(true)
-				.write(true)
-				.truncate(true)
-				.open(&archive_path)
-				.context(format!("open file {archive_path:?}"))?;
-			let mut archive = zip::ZipWriter::new(out_file);
+			// The archive is built in memory so that it is only written through the runner.
+			let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
 			let options = zip::write::FileOptions::default();
 			for lang_dir_abs in lang_paths.into_iter() {
 				let lang_name = lang_dir_abs.strip_prefix(&loc_root)?.to_str().unwrap();
@@ -283,10 +302,14 @@ impl crate::Operation for ExportPOZip {
 
 				let po_path_abs = lang_dir_abs.join("Game.po");
 				let po_bytes = tokio::fs::read(&po_path_abs).await?;
-				archive.start_file(&format!("{lang_name}/Game.po"), options)?;
+				archive.start_file(format!("{lang_name}/Game.po"), options)?;
 				archive.write_all(&po_bytes[..])?;
 			}
-			archive.finish()?;
+			let archive_bytes = archive.finish()?.into_inner();
+			runner
+				.write_file(archive_path.clone(), archive_bytes)
+				.await
+				.context(format!("write file {archive_path:?}"))?;
 
 			Ok(())
 		})
@@ -301,28 +324,46 @@ pub struct ImportPOZip {
 }
 
 impl crate::Operation for ImportPOZip {
-	fn run(self, config: crate::config::Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			let import_source_dir = Import::get_source_path(&config)
+			let config = session.config();
+			let runner = session.runner();
+			let import_source_dir = Import::get_source_path(config)
 				.await
 				.context("get import source path")?;
 			let import_source_dir = config.project_root().join(import_source_dir);
-			std::fs::create_dir_all(&import_source_dir).context("create import dir")?;
-
-			self.extract_zip_to(&import_source_dir)
-				.context("extract zip")?;
+			runner
+				.create_dir_all(import_source_dir.clone())
+				.await
+				.context("create import dir")?;
+
+			let po_files = self.read_po_files().context("extract zip")?;
+			for (lang_name, po_bytes) in po_files.into_iter() {
+				let lang_dir = import_source_dir.join(lang_name);
+				runner.create_dir_all(lang_dir.clone()).await?;
+				let target_file_path = lang_dir.join("Game.po");
+				runner
+					.write_file(target_file_path.clone(), po_bytes)
+					.await
+					.context(format!("write to {target_file_path:?}"))?;
+			}
 
-			Import { lang: None }.run(config).await.context("import")?;
+			Import { lang: None }
+				.run(session.clone())
+				.await
+				.context("import")?;
 
-			std::fs::remove_dir_all(&import_source_dir)?;
+			runner.remove_dir_all(import_source_dir).await?;
 			Ok(())
 		})
 	}
 }
 impl ImportPOZip {
-	fn extract_zip_to(&self, target_dir: &std::path::Path) -> anyhow::Result<()> {
+	/// Reads the contents of every `{lang}/Game.po` in the zip, keyed by the language name.
+	fn read_po_files(&self) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
 		let file = std::fs::File::open(&self.zip_path)?;
 		let mut archive = zip::ZipArchive::new(file).unwrap();
+		let mut po_files = Vec::new();
 		for i in 0..archive.len() {
 			let mut entry = archive.by_index(i).unwrap();
 			if !entry.is_file() {
@@ -342,18 +383,92 @@ impl ImportPOZip {
 				.unwrap()
 				.to_str()
 				.unwrap();
-			let lang_dir = target_dir.join(lang_name);
-			std::fs::create_dir_all(&lang_dir)?;
-			let target_file_path = lang_dir.join("Game.po");
-			let mut target_file = std::fs::File::options()
-				.create(true)
-				.write(true)
-				.truncate(true)
-				.open(&target_file_path)
-				.context(format!("open {target_file_path:?}"))?;
-			std::io::copy(&mut entry, &mut target_file)
-				.context(format!("write to {target_file_path:?}"))?;
+			let mut po_bytes = Vec::new();
+			std::io::copy(&mut entry, &mut po_bytes)
+				.context(format!("read {path_rel:?} from zip"))?;
+			po_files.push((lang_name.to_owned(), po_bytes));
 		}
-		Ok(())
+		Ok(po_files)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		runner::{FileMutation, RecordingRunner},
+		test_support::{session, temp_dir},
+		unreal::UProject,
+		Operation,
+	};
+	use std::sync::Arc;
+
+	#[tokio::test]
+	async fn gather_runs_editor_commandlet() {
+		let root = PathBuf::from("/projects/Game");
+		let runner = Arc::new(RecordingRunner::new());
+		Gather { lang: None }
+			.run(session(&root, UProject::default(), &runner))
+			.await
+			.unwrap();
+
+		let commands = runner.commands();
+		assert_eq!(commands.len(), 1);
+		assert_eq!(
+			commands[0].program,
+			"/engine/Binaries/Linux/UE4Editor".to_owned()
+		);
+		assert_eq!(
+			commands[0].args,
+			[
+				"/projects/Game/Game.uproject",
+				"-run=GatherText",
+				"-config=Config/Localization/Game_Gather.ini",
+				"-EnableSCC",
+				"-DisableSCCSubmit",
+			]
+		);
+		assert_eq!(commands[0].current_dir, Some(root));
+		assert!(runner.mutations().is_empty());
+	}
+
+	#[tokio::test]
+	async fn single_language_uses_temporary_config() {
+		let root = temp_dir("localization-lang");
+		let loc_dir = root.join("Config/Localization");
+		std::fs::create_dir_all(&loc_dir).unwrap();
+		std::fs::write(
+			loc_dir.join("Game_Compile.ini"),
+			"[CommonSettings]\nCulturesToGenerate=en\nCulturesToGenerate=fr\n",
+		)
+		.unwrap();
+
+		let runner = Arc::new(RecordingRunner::new());
+		Compile {
+			lang: Some("fr".to_owned()),
+		}
+		.run(session(&root, UProject::default(), &runner))
+		.await
+		.unwrap();
+
+		let commands = runner.commands();
+		assert_eq!(commands.len(), 1);
+		assert_eq!(
+			commands[0].args[2],
+			"-config=Config/Localization/Game_Compile_fr.ini"
+		);
+		assert_eq!(commands[0].current_dir, Some(root.clone()));
+		let temporary_path = loc_dir.join("Game_Compile_fr.ini");
+		assert_eq!(
+			runner.mutations(),
+			[
+				FileMutation::Write {
+					path: temporary_path.clone(),
+					contents: b"[CommonSettings]\nCulturesToGenerate=fr\n".to_vec(),
+				},
+				FileMutation::RemoveFile(temporary_path),
+			]
+		);
+		std::fs::remove_dir_all(&root).unwrap();
 	}
 }
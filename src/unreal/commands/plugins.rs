@@ -0,0 +1,420 @@
+use crate::{
+	config::Config,
+	output::{emit_data, OutputFormat},
+	types::Platform,
+	unreal::{find_plugin_descriptors, Plugin, UPlugin, UProject},
+	utility::AsUnrealStr,
+};
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Subcommands to inspect and toggle the plugins referenced by the project.
+#[derive(Subcommand, Debug)]
+pub enum Plugins {
+	List(List),
+	Enable(Enable),
+	Disable(Disable),
+	Validate(Validate),
+}
+
+impl crate::Operation for Plugins {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		match self {
+			Self::List(cmd) => cmd.run(session),
+			Self::Enable(cmd) => cmd.run(session),
+			Self::Disable(cmd) => cmd.run(session),
+			Self::Validate(cmd) => cmd.run(session),
+		}
+	}
+}
+
+/// Where a plugin's descriptor was found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PluginSource {
+	/// Under the project's `Plugins` directory.
+	Project,
+	/// Under the engine's `Plugins` directory.
+	Engine,
+}
+
+impl std::fmt::Display for PluginSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Project => write!(f, "project"),
+			Self::Engine => write!(f, "engine"),
+		}
+	}
+}
+
+/// A plugin descriptor found on disk.
+struct InstalledPlugin {
+	source: PluginSource,
+	path: PathBuf,
+}
+
+/// All plugins available to the project, keyed by name.
+/// Project plugins take priority over engine plugins with the same name.
+fn find_installed_plugins(config: &Config) -> BTreeMap<String, InstalledPlugin> {
+	let mut installed = BTreeMap::new();
+	for path in find_plugin_descriptors(&config.engine_path().join("Plugins")) {
+		let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+			continue;
+		};
+		let source = PluginSource::Engine;
+		installed.insert(name.to_owned(), InstalledPlugin { source, path });
+	}
+	for plugin in config.plugins().iter() {
+		let source = PluginSource::Project;
+		let path = plugin.descriptor_path.clone();
+		installed.insert(plugin.name.clone(), InstalledPlugin { source, path });
+	}
+	installed
+}
+
+/// Names of plugins referenced by the project which could not be found on disk.
+fn find_missing_plugins(
+	project: &UProject,
+	installed: &BTreeMap<String, InstalledPlugin>,
+) -> Vec<String> {
+	project
+		.plugins
+		.iter()
+		.filter(|plugin| !installed.contains_key(&plugin.name))
+		.map(|plugin| plugin.name.clone())
+		.collect()
+}
+
+async fn read_project(config: &Config) -> anyhow::Result<(PathBuf, UProject)> {
+	let path = config.uproject_path();
+	let project = UProject::read(&path).await.context("read uproject")?;
+	Ok((path, project))
+}
+
+/// Finds the project's reference to the plugin, adding one with the `enabled` state if there is none.
+fn find_or_insert_plugin<'p>(
+	project: &'p mut UProject,
+	name: &str,
+	enabled: bool,
+) -> &'p mut Plugin {
+	let idx = match project
+		.plugins
+		.iter()
+		.position(|plugin| plugin.name == name)
+	{
+		Some(idx) => idx,
+		None => {
+			project.plugins.push(Plugin {
+				name: name.to_owned(),
+				enabled: Some(enabled),
+				..Default::default()
+			});
+			project.plugins.len() - 1
+		}
+	};
+	&mut project.plugins[idx]
+}
+
+fn warn_if_missing(config: &Config, name: &str) {
+	if !find_installed_plugins(config).contains_key(name) {
+		tracing::warn!(
+			"Plugin {name:?} was not found in the project or engine Plugins directories"
+		);
+	}
+}
+
+/// A row of `plugin list`.
+#[derive(Serialize)]
+struct PluginEntry {
+	name: String,
+	/// `enabled` or `disabled` if the uproject references the plugin, otherwise `default`.
+	state: &'static str,
+	source: String,
+	version: String,
+	platforms: Vec<String>,
+	excluded_platforms: Vec<String>,
+	targets: Vec<String>,
+}
+
+/// Lists the project's plugins, their enabled state, where they are installed, and their version.
+#[derive(Parser, Debug)]
+pub struct List {
+	/// Also list installed plugins which are not referenced by the uproject.
+	#[clap(short, long)]
+	all: bool,
+}
+
+impl crate::Operation for List {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let (_, project) = read_project(config).await?;
+			let installed = find_installed_plugins(config);
+
+			let mut names = project
+				.plugins
+				.iter()
+				.map(|plugin| plugin.name.clone())
+				.collect::<Vec<_>>();
+			if self.all {
+				names.extend(installed.keys().cloned());
+			} else {
+				// Project plugins are enabled by default, so they are always relevant.
+				names.extend(
+					installed
+						.iter()
+						.filter(|(_, plugin)| plugin.source == PluginSource::Project)
+						.map(|(name, _)| name.clone()),
+				);
+			}
+			names.sort();
+			names.dedup();
+
+			let mut entries = Vec::new();
+			for name in names.into_iter() {
+				let reference = project.plugins.iter().find(|plugin| plugin.name == name);
+				let state = match reference {
+					Some(plugin) if plugin.is_enabled() => "enabled",
+					Some(_) => "disabled",
+					None => "default",
+				};
+				let (source, version) = match installed.get(&name) {
+					Some(plugin) => {
+						let version = match UPlugin::read(&plugin.path).await {
+							Ok(descriptor) if !descriptor.version_name.is_empty() => {
+								format!("v{}", descriptor.version_name)
+							}
+							Ok(descriptor) => format!("v{}", descriptor.version),
+							Err(_) => "unknown version".to_owned(),
+						};
+						(plugin.source.to_string(), version)
+					}
+					None => ("missing".to_owned(), "unknown version".to_owned()),
+				};
+				entries.push(PluginEntry {
+					name,
+					state,
+					source,
+					version,
+					platforms: reference
+						.map(|plugin| plugin.platform_allow_list.clone())
+						.unwrap_or_default(),
+					excluded_platforms: reference
+						.map(|plugin| plugin.platform_deny_list.clone())
+						.unwrap_or_default(),
+					targets: reference
+						.map(|plugin| plugin.target_allow_list.clone())
+						.unwrap_or_default(),
+				});
+			}
+			let missing = find_missing_plugins(&project, &installed);
+
+			if session.output() == OutputFormat::Json {
+				return emit_data(&json!({ "plugins": entries, "missing": missing }));
+			}
+			println!("Plugins:");
+			for entry in entries.iter() {
+				println!(
+					"  {}: {} ({}, {})",
+					entry.name, entry.state, entry.source, entry.version
+				);
+				if !entry.platforms.is_empty() {
+					println!("    Platforms: {}", entry.platforms.join(", "));
+				}
+				if !entry.excluded_platforms.is_empty() {
+					println!(
+						"    Excluded Platforms: {}",
+						entry.excluded_platforms.join(", ")
+					);
+				}
+				if !entry.targets.is_empty() {
+					println!("    Targets: {}", entry.targets.join(", "));
+				}
+			}
+			if !missing.is_empty() {
+				tracing::warn!(
+					"The uproject references plugins which are not installed: {}",
+					missing.join(", ")
+				);
+			}
+			Ok(())
+		})
+	}
+}
+
+/// Enables a plugin in the uproject, optionally only for specific platforms.
+#[derive(Parser, Debug)]
+pub struct Enable {
+	name: String,
+	/// Enable the plugin only for these platforms (adds them to the plugin's platform allow list).
+	#[clap(short, long, value_enum)]
+	platform: Vec<Platform>,
+}
+
+impl crate::Operation for Enable {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
+			warn_if_missing(config, &self.name);
+			let (path, mut project) = read_project(config).await?;
+			enable_plugin(&mut project, &self.name, &self.platform);
+			tracing::info!("Enabling plugin {:?} in {}", self.name, path.display());
+			project.write(&path, runner).await?;
+			Ok(())
+		})
+	}
+}
+
+/// Disables a plugin in the uproject, or removes specific platforms from it.
+#[derive(Parser, Debug)]
+pub struct Disable {
+	name: String,
+	/// Disable the plugin only for these platforms, leaving it enabled on the others.
+	#[clap(short, long, value_enum)]
+	platform: Vec<Platform>,
+}
+
+impl crate::Operation for Disable {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
+			warn_if_missing(config, &self.name);
+			let (path, mut project) = read_project(config).await?;
+			disable_plugin(&mut project, &self.name, &self.platform);
+			tracing::info!("Disabling plugin {:?} in {}", self.name, path.display());
+			project.write(&path, runner).await?;
+			Ok(())
+		})
+	}
+}
+
+/// Enables the plugin, only for the platforms if any are provided.
+fn enable_plugin(project: &mut UProject, name: &str, platforms: &[Platform]) {
+	let plugin = find_or_insert_plugin(project, name, true);
+	plugin.enabled = Some(true);
+	for platform in platforms.iter() {
+		let platform = platform.as_ue().to_owned();
+		plugin
+			.platform_deny_list
+			.retain(|denied| *denied != platform);
+		if !plugin.platform_allow_list.contains(&platform) {
+			plugin.platform_allow_list.push(platform);
+		}
+	}
+}
+
+/// Disables the plugin, or only the platforms if any are provided.
+fn disable_plugin(project: &mut UProject, name: &str, platforms: &[Platform]) {
+	// A new reference for specific platforms starts enabled, so that it is only disabled on those platforms.
+	let plugin = find_or_insert_plugin(project, name, !platforms.is_empty());
+	if platforms.is_empty() {
+		plugin.enabled = Some(false);
+		return;
+	}
+	for platform in platforms.iter() {
+		let platform = platform.as_ue().to_owned();
+		// An allow list already excludes every platform not in it.
+		if plugin.platform_allow_list.is_empty() {
+			if !plugin.platform_deny_list.contains(&platform) {
+				plugin.platform_deny_list.push(platform);
+			}
+		} else {
+			plugin
+				.platform_allow_list
+				.retain(|allowed| *allowed != platform);
+			// An empty allow list would allow every platform.
+			if plugin.platform_allow_list.is_empty() {
+				plugin.enabled = Some(false);
+			}
+		}
+	}
+}
+
+/// Checks that every plugin referenced by the uproject is installed in the project or engine.
+#[derive(Parser, Debug)]
+pub struct Validate;
+
+impl crate::Operation for Validate {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
+		Box::pin(async move {
+			let config = session.config();
+			let (_, project) = read_project(config).await?;
+			let installed = find_installed_plugins(config);
+			let missing = find_missing_plugins(&project, &installed);
+			if !missing.is_empty() {
+				return Err(MissingPlugins(missing))?;
+			}
+			tracing::info!(
+				"All {} referenced plugins were found.",
+				project.plugins.len()
+			);
+			Ok(())
+		})
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct MissingPlugins(Vec<String>);
+impl std::fmt::Display for MissingPlugins {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"The uproject references plugins which are not installed: {}",
+			self.0.join(", ")
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn plugin<'p>(project: &'p UProject, name: &str) -> &'p Plugin {
+		project
+			.plugins
+			.iter()
+			.find(|plugin| plugin.name == name)
+			.unwrap()
+	}
+
+	#[test]
+	fn disable_platform_of_unreferenced_plugin_keeps_it_enabled_elsewhere() {
+		let mut project = UProject::default();
+		disable_plugin(&mut project, "Foo", &[Platform::Linux]);
+		let foo = plugin(&project, "Foo");
+		assert!(foo.is_enabled());
+		assert_eq!(foo.platform_deny_list, vec!["Linux".to_owned()]);
+		assert!(foo.platform_allow_list.is_empty());
+	}
+
+	#[test]
+	fn disable_last_allowed_platform_disables_plugin() {
+		let mut project = UProject::default();
+		enable_plugin(&mut project, "Foo", &[Platform::Windows]);
+		disable_plugin(&mut project, "Foo", &[Platform::Windows]);
+		let foo = plugin(&project, "Foo");
+		assert!(!foo.is_enabled());
+		assert!(foo.platform_allow_list.is_empty());
+	}
+
+	#[test]
+	fn disable_one_of_several_allowed_platforms() {
+		let mut project = UProject::default();
+		enable_plugin(&mut project, "Foo", &[Platform::Windows, Platform::Linux]);
+		disable_plugin(&mut project, "Foo", &[Platform::Windows]);
+		let foo = plugin(&project, "Foo");
+		assert!(foo.is_enabled());
+		assert_eq!(foo.platform_allow_list, vec!["Linux".to_owned()]);
+	}
+
+	#[test]
+	fn disable_without_platforms_disables_everywhere() {
+		let mut project = UProject::default();
+		disable_plugin(&mut project, "Foo", &[]);
+		assert!(!plugin(&project, "Foo").is_enabled());
+	}
+}
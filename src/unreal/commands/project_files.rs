@@ -1,4 +1,4 @@
-use crate::{config::Config, utility::spawn_command};
+use crate::types::HostPlatform;
 use clap::Parser;
 use tokio::process::Command;
 
@@ -7,13 +7,15 @@ use tokio::process::Command;
 pub struct UpdateProjectFiles;
 
 impl crate::Operation for UpdateProjectFiles {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
 			let root = config.project_root();
-			let batch = config
+			let script = config
 				.engine_path()
-				.join("Build/BatchFiles/GenerateProjectFiles.bat");
-			spawn_command(Command::new(batch).current_dir(root)).await?;
+				.join(HostPlatform::current().generate_project_files_script());
+			runner.spawn(Command::new(script).current_dir(root)).await?;
 			Ok(())
 		})
 	}
@@ -1,4 +1,3 @@
-use crate::{config::Config, utility::spawn_command};
 use clap::Parser;
 use tokio::process::Command;
 
@@ -7,16 +6,19 @@ use tokio::process::Command;
 pub struct RunEditor;
 
 impl crate::Operation for RunEditor {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
-			spawn_command(
-				Command::new(config.editor_binary())
-					.current_dir(config.project_root())
-					.arg(config.uproject_path())
-					.arg("-debug")
-					.args(&["-stdout", "-AllowStdOutLogVerbosity"]),
-			)
-			.await?;
+			let config = session.config();
+			let runner = session.runner();
+			runner
+				.spawn(
+					Command::new(config.editor_binary())
+						.current_dir(config.project_root())
+						.arg(config.uproject_path())
+						.arg("-debug")
+						.args(["-stdout", "-AllowStdOutLogVerbosity"]),
+				)
+				.await?;
 			Ok(())
 		})
 	}
@@ -1,9 +1,7 @@
 use std::path::PathBuf;
 
 use crate::{
-	config::{self, Config},
-	types::Configuration,
-	utility::{spawn_command, AsUnrealStr},
+	config::Config, session::require_project, types::Configuration, utility::AsUnrealStr, Session,
 };
 use clap::{builder::StringValueParser, Parser};
 use tokio::process::Command;
@@ -23,16 +21,17 @@ pub struct RunPisep {
 
 	/// The unreal map level to open when the game begins.
 	/// Defaults to the level setting in user preferences based on if this is a server or not.
-	#[clap(long, value_parser=MapValueParser)]
+	#[clap(long, value_parser = MapValueParser::default())]
 	level: Option<PathBuf>,
 	/// The game mode alias to run in the level.
 	/// Ignored if level is not provided.
-	#[clap(long, value_parser=ModeValueParser)]
+	#[clap(long, value_parser = ModeValueParser::default())]
 	mode: Option<String>,
 }
 
-#[derive(Clone, Debug)]
-struct MapValueParser;
+/// Parses a map by name or package using the session's project.
+#[derive(Clone, Debug, Default)]
+struct MapValueParser(Option<Session>);
 impl clap::builder::TypedValueParser for MapValueParser {
 	type Value = PathBuf;
 
@@ -43,34 +42,55 @@ impl clap::builder::TypedValueParser for MapValueParser {
 		value: &std::ffi::OsStr,
 	) -> Result<Self::Value, clap::Error> {
 		let val = StringValueParser::new().parse_ref(cmd, arg, value)?;
-		let cfg = config::Config::get_global();
-		match cfg.game().maps_by_name().get(&val) {
-			Some(&path) => {
-				// By unreal convection, map names should be suffixed with `.name`
-				// e.g. "/Game/Maps/Level1" => "/Game/Maps/Level1.Level1"
-				let map_name = path.file_name().unwrap().to_str().unwrap();
-				Ok(path.with_extension(map_name))
+		let cfg = require_project(self.0.as_ref(), cmd, arg)?;
+		match cfg.game().find_map(&val) {
+			Some(map) => Ok(PathBuf::from(map.object_path())),
+			None => {
+				let arg = arg.map(ToString::to_string).unwrap_or_default();
+				let ambiguous = cfg
+					.game()
+					.maps()
+					.iter()
+					.filter(|map| map.name().eq_ignore_ascii_case(&val))
+					.map(|map| map.package.as_str())
+					.collect::<Vec<_>>();
+				let message = match ambiguous.is_empty() {
+					true => {
+						let known = cfg.game().maps_by_name().into_keys().collect::<Vec<_>>();
+						format!(
+							"invalid value '{val}' for '{arg}': no map with that name or package was found in Content/ or plugin content.\n\
+							Known maps: {}",
+							known.join(", ")
+						)
+					}
+					false => format!(
+						"invalid value '{val}' for '{arg}': several maps are named '{val}', use one of their package names instead: {}",
+						ambiguous.join(", ")
+					),
+				};
+				Err(cmd
+					.clone()
+					.error(clap::error::ErrorKind::InvalidValue, message))
 			}
-			None => Err(clap::Error::new(clap::error::ErrorKind::InvalidValue)),
 		}
 	}
 
 	fn possible_values(
 		&self,
 	) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
-		let cfg = config::Config::get_global();
+		let cfg = self.0.as_ref()?.config();
 		Some(Box::new(
 			cfg.game()
 				.maps_by_name()
-				.into_iter()
-				.map(|(name, _)| name)
+				.into_keys()
 				.map(clap::builder::PossibleValue::new),
 		))
 	}
 }
 
-#[derive(Clone, Debug)]
-struct ModeValueParser;
+/// Parses a game mode alias, listing the session project's aliases as possible values.
+#[derive(Clone, Debug, Default)]
+struct ModeValueParser(Option<Session>);
 impl clap::builder::TypedValueParser for ModeValueParser {
 	type Value = String;
 
@@ -80,13 +100,14 @@ impl clap::builder::TypedValueParser for ModeValueParser {
 		arg: Option<&clap::Arg>,
 		value: &std::ffi::OsStr,
 	) -> Result<Self::Value, clap::Error> {
+		require_project(self.0.as_ref(), cmd, arg)?;
 		StringValueParser::new().parse_ref(cmd, arg, value)
 	}
 
 	fn possible_values(
 		&self,
 	) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
-		let cfg = config::Config::get_global();
+		let cfg = self.0.as_ref()?.config();
 		Some(Box::new(
 			cfg.engine()
 				.mode_aliases()
@@ -97,6 +118,16 @@ impl clap::builder::TypedValueParser for ModeValueParser {
 }
 
 impl RunPisep {
+	/// Gives the value parsers of the `pisep` subcommand access to the session's project.
+	pub(crate) fn bind_session(cmd: clap::Command, session: &Session) -> clap::Command {
+		cmd.mut_arg("level", |arg| {
+			arg.value_parser(MapValueParser(Some(session.clone())))
+		})
+		.mut_arg("mode", |arg| {
+			arg.value_parser(ModeValueParser(Some(session.clone())))
+		})
+	}
+
 	fn get_level_arg(&self, config: &Config) -> anyhow::Result<Option<String>> {
 		let mut level_arg = Vec::with_capacity(3);
 		// Add the level to load
@@ -123,22 +154,24 @@ impl RunPisep {
 }
 
 impl crate::Operation for RunPisep {
-	fn run(self, config: Config) -> crate::utility::PinFuture<anyhow::Result<()>> {
+	fn run(self, session: crate::Session) -> crate::utility::PinFuture<anyhow::Result<()>> {
 		Box::pin(async move {
+			let config = session.config();
+			let runner = session.runner();
 			let mut cmd = Command::new(config.editor_binary());
 			cmd.current_dir(config.project_root())
 				.arg(config.uproject_path());
 
 			cmd.arg(if self.server { "-server" } else { "-game" });
-			if let Some(arg) = self.get_level_arg(&config)? {
+			if let Some(arg) = self.get_level_arg(config)? {
 				cmd.arg(arg);
 			}
-			cmd.args(&["-stdout", "-AllowStdOutLogVerbosity"]);
-			cmd.args(&["-NoEAC", "-messaging"]);
+			cmd.args(["-stdout", "-AllowStdOutLogVerbosity"]);
+			cmd.args(["-NoEAC", "-messaging"]);
 			cmd.arg(format!("RunConfig={}", self.configuration.as_ue()));
 			cmd.arg("-debug");
 
-			spawn_command(&mut cmd).await?;
+			runner.spawn(&mut cmd).await?;
 			Ok(())
 		})
 	}
@@ -1,3 +1,4 @@
+use crate::runner::SharedRunner;
 use serde::{Deserialize, Serialize};
 use std::{
 	collections::HashMap,
@@ -18,17 +19,21 @@ impl BinaryModule {
 		Ok(serde_json::from_str::<Self>(&json)?)
 	}
 
-	pub async fn write(&self, path: &Path) -> anyhow::Result<()> {
+	pub async fn write(&self, path: &Path, runner: &SharedRunner) -> anyhow::Result<()> {
 		let json = serde_json::to_string_pretty(&self)?;
 		let json = json.replace("  ", "\t");
-		tokio::fs::write(&path, json).await?;
+		runner
+			.write_file(path.to_owned(), json.into_bytes())
+			.await?;
 		Ok(())
 	}
 
-	pub fn dealias(&mut self) -> Vec<(PathBuf, PathBuf)> {
+	/// Renames each module's binary to the editor's naming convention (`{editor_name}-{module}.dll`),
+	/// returning the (existing, desired) pairs of binaries which need to be moved.
+	pub fn dealias(&mut self, editor_name: &str) -> Vec<(PathBuf, PathBuf)> {
 		let mut redirectors = Vec::new();
 		for (module_name, binary_pathname) in self.modules.iter_mut() {
-			let desired_name = format!("UE4Editor-{}.dll", module_name);
+			let desired_name = format!("{editor_name}-{module_name}.dll");
 			if *binary_pathname != desired_name {
 				let mut existing_path = PathBuf::from(binary_pathname.clone());
 				let mut desired_path = PathBuf::from(desired_name.clone());
@@ -0,0 +1,242 @@
+use super::ini::IniFile;
+use crate::{types::HostPlatform, utility::home_dir};
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The environment variable which explicitly provides the engine to use, overriding the project's association.
+pub const ENGINE_ROOT_VAR: &str = "UE_ENGINE_ROOT";
+
+/// Where an [`EngineInstall`] was discovered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EngineSource {
+	/// The [`ENGINE_ROOT_VAR`] environment variable.
+	Environment,
+	/// The Epic Games Launcher's `LauncherInstalled.dat`.
+	Launcher,
+	/// A source build registered by GUID in `Install.ini`.
+	InstallIni,
+}
+
+impl std::fmt::Display for EngineSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Environment => write!(f, "{ENGINE_ROOT_VAR}"),
+			Self::Launcher => write!(f, "launcher"),
+			Self::InstallIni => write!(f, "Install.ini"),
+		}
+	}
+}
+
+/// An engine installed on this machine.
+#[derive(Clone, Debug)]
+pub struct EngineInstall {
+	/// The value of a uproject's `EngineAssociation` which refers to this engine,
+	/// either a version (e.g. "5.1") or a GUID (e.g. "{1C2B3A4D-...}").
+	pub association: String,
+	/// The directory which contains the `Engine` directory.
+	pub root: PathBuf,
+	pub source: EngineSource,
+}
+
+impl EngineInstall {
+	/// The `Engine` directory of this install.
+	pub fn engine_dir(&self) -> PathBuf {
+		engine_dir_of(&self.root)
+	}
+
+	fn matches(&self, association: &str) -> bool {
+		normalize_association(&self.association) == normalize_association(association)
+	}
+}
+
+/// All engines that uebuild can find on this machine.
+#[derive(Clone, Debug, Default)]
+pub struct EngineRegistry {
+	installs: Vec<EngineInstall>,
+}
+
+impl EngineRegistry {
+	/// Reads the environment, launcher manifest, and `Install.ini` registry for installed engines.
+	/// Sources which do not exist or cannot be parsed are skipped.
+	pub async fn discover() -> Self {
+		let host = HostPlatform::current();
+		let mut installs = Vec::new();
+
+		if let Ok(root) = std::env::var(ENGINE_ROOT_VAR) {
+			installs.push(EngineInstall {
+				association: ENGINE_ROOT_VAR.to_owned(),
+				root: PathBuf::from(root),
+				source: EngineSource::Environment,
+			});
+		}
+
+		if let Some(path) = launcher_installed_path(host) {
+			if path.exists() {
+				match read_launcher_installed(&path).await {
+					Ok(mut launcher) => installs.append(&mut launcher),
+					Err(err) => tracing::warn!("Failed to read {}: {err:#}", path.display()),
+				}
+			}
+		}
+
+		if let Some(path) = install_ini_path(host) {
+			if path.exists() {
+				match read_install_ini(&path).await {
+					Ok(mut registered) => installs.append(&mut registered),
+					Err(err) => tracing::warn!("Failed to read {}: {err:#}", path.display()),
+				}
+			}
+		}
+
+		Self { installs }
+	}
+
+	pub fn installs(&self) -> &Vec<EngineInstall> {
+		&self.installs
+	}
+
+	/// Finds the `Engine` directory for a project with the provided `EngineAssociation`.
+	///
+	/// An engine provided by [`ENGINE_ROOT_VAR`] always wins.
+	/// Projects without an association are expected to sit next to a source engine (`{project}/../Engine`).
+	/// Otherwise the association is matched against discovered installs,
+	/// falling back to the launcher's default install location for the host.
+	pub fn resolve(&self, association: Option<&String>, project_root: &Path) -> Option<PathBuf> {
+		let explicit = self
+			.installs
+			.iter()
+			.find(|install| install.source == EngineSource::Environment);
+		if let Some(install) = explicit {
+			return Some(install.engine_dir());
+		}
+
+		let Some(association) = association else {
+			let path = project_root.parent()?.join("Engine");
+			return match path.exists() {
+				true => Some(path),
+				false => {
+					tracing::warn!("Failed to find custom engine at {}", path.display());
+					None
+				}
+			};
+		};
+
+		if let Some(install) = self
+			.installs
+			.iter()
+			.find(|install| install.matches(association))
+		{
+			return Some(install.engine_dir());
+		}
+
+		let path = HostPlatform::current().default_install_dir(association)?;
+		match path.exists() {
+			true => Some(path),
+			false => {
+				tracing::warn!(
+					"Failed to find an installed engine for association {association:?}"
+				);
+				None
+			}
+		}
+	}
+}
+
+/// Accepts either an engine root or the `Engine` directory itself.
+fn engine_dir_of(root: &Path) -> PathBuf {
+	let engine_dir = root.join("Engine");
+	match engine_dir.exists() || !root.ends_with("Engine") {
+		true => engine_dir,
+		false => root.to_owned(),
+	}
+}
+
+/// GUID associations are written with or without braces and in any case.
+fn normalize_association(association: &str) -> String {
+	association
+		.trim()
+		.trim_start_matches('{')
+		.trim_end_matches('}')
+		.to_lowercase()
+}
+
+fn launcher_installed_path(host: HostPlatform) -> Option<PathBuf> {
+	let relative = "Epic/UnrealEngineLauncher/LauncherInstalled.dat";
+	match host {
+		HostPlatform::Windows => {
+			let program_data =
+				std::env::var("PROGRAMDATA").unwrap_or_else(|_| "C:\\ProgramData".to_owned());
+			Some(PathBuf::from(program_data).join(relative))
+		}
+		HostPlatform::Mac => Some(
+			home_dir()?
+				.join("Library/Application Support")
+				.join(relative),
+		),
+		HostPlatform::Linux => None,
+	}
+}
+
+fn install_ini_path(host: HostPlatform) -> Option<PathBuf> {
+	let relative = "Epic/UnrealEngine/Install.ini";
+	match host {
+		// Windows source builds are registered in the system registry instead.
+		HostPlatform::Windows => None,
+		HostPlatform::Mac => Some(
+			home_dir()?
+				.join("Library/Application Support")
+				.join(relative),
+		),
+		HostPlatform::Linux => Some(home_dir()?.join(".config").join(relative)),
+	}
+}
+
+#[derive(Deserialize)]
+struct LauncherInstalled {
+	#[serde(rename = "InstallationList")]
+	installation_list: Vec<LauncherItem>,
+}
+
+#[derive(Deserialize)]
+struct LauncherItem {
+	#[serde(rename = "InstallLocation")]
+	install_location: PathBuf,
+	#[serde(rename = "AppName")]
+	app_name: String,
+}
+
+async fn read_launcher_installed(path: &Path) -> anyhow::Result<Vec<EngineInstall>> {
+	let json = tokio::fs::read_to_string(path)
+		.await
+		.context(format!("read {path:?}"))?;
+	let launcher = serde_json::from_str::<LauncherInstalled>(&json)?;
+	// The launcher also tracks games and marketplace content, engines are named like "UE_5.1".
+	Ok(launcher
+		.installation_list
+		.into_iter()
+		.filter_map(|item| {
+			let version = item.app_name.strip_prefix("UE_")?;
+			Some(EngineInstall {
+				association: version.to_owned(),
+				root: item.install_location,
+				source: EngineSource::Launcher,
+			})
+		})
+		.collect())
+}
+
+async fn read_install_ini(path: &Path) -> anyhow::Result<Vec<EngineInstall>> {
+	let text = tokio::fs::read_to_string(path)
+		.await
+		.context(format!("read {path:?}"))?;
+	let content = IniFile::parse(&text);
+	Ok(content
+		.section_entries("Installations")
+		.map(|entry| EngineInstall {
+			association: entry.key.clone(),
+			root: PathBuf::from(&entry.value),
+			source: EngineSource::InstallIni,
+		})
+		.collect())
+}
@@ -0,0 +1,62 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The base name of the editor binaries, targets and modules files in Unreal Engine 4.
+pub const UE4_EDITOR_NAME: &str = "UE4Editor";
+/// The base name of the editor binaries, targets and modules files in Unreal Engine 5 and later.
+pub const UE5_EDITOR_NAME: &str = "UnrealEditor";
+
+/// The version of an engine, as described by its `Engine/Build/Build.version` file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineVersion {
+	#[serde(rename = "MajorVersion")]
+	pub major: u32,
+	#[serde(rename = "MinorVersion")]
+	pub minor: u32,
+	#[serde(rename = "PatchVersion")]
+	pub patch: u32,
+	#[serde(rename = "Changelist", default)]
+	pub changelist: u32,
+	#[serde(rename = "CompatibleChangelist", default)]
+	pub compatible_changelist: u32,
+	#[serde(rename = "IsLicenseeVersion", default)]
+	pub is_licensee_version: u32,
+	#[serde(rename = "IsPromotedBuild", default)]
+	pub is_promoted_build: u32,
+	#[serde(rename = "BranchName", default)]
+	pub branch_name: String,
+}
+
+impl EngineVersion {
+	/// Reads `Build/Build.version` from the provided `Engine` directory.
+	pub async fn read(engine_path: &Path) -> anyhow::Result<Self> {
+		let path = engine_path.join("Build/Build.version");
+		let json = tokio::fs::read_to_string(&path)
+			.await
+			.context(format!("read {:?}", path))?;
+		Ok(serde_json::from_str::<Self>(&json)?)
+	}
+
+	/// The base name of the editor binaries, targets and modules files for this engine
+	/// (e.g. `UE4Editor-Win64-DebugGame.exe` vs `UnrealEditor-Win64-DebugGame.exe`).
+	pub fn editor_name(&self) -> &'static str {
+		match self.major {
+			0..=4 => UE4_EDITOR_NAME,
+			_ => UE5_EDITOR_NAME,
+		}
+	}
+}
+
+impl std::fmt::Display for EngineVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+		if self.changelist != 0 {
+			write!(f, "-{}", self.changelist)?;
+		}
+		if !self.branch_name.is_empty() {
+			write!(f, "+{}", self.branch_name)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,7 @@
+//! Reading and merging Unreal's `.ini` config files.
+mod file;
+pub use file::*;
+mod hierarchy;
+pub use hierarchy::*;
+mod value;
+pub use value::*;
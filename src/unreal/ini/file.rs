@@ -0,0 +1,420 @@
+use anyhow::Context;
+use std::path::Path;
+
+/// How an entry modifies the values of its key when the file is merged into a hierarchy.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IniOp {
+	/// `Key=Value`: replaces any values from earlier layers.
+	Set,
+	/// `+Key=Value`: adds the value to the array if it is not already present.
+	Add,
+	/// `.Key=Value`: adds the value to the array, even if it is already present.
+	AddDuplicate,
+	/// `-Key=Value`: removes the first matching value from the array.
+	Remove,
+	/// `!Key=ClearArray`: removes all values of the key.
+	Clear,
+}
+
+impl IniOp {
+	fn from_prefix(prefix: char) -> Option<Self> {
+		match prefix {
+			'+' => Some(Self::Add),
+			'.' => Some(Self::AddDuplicate),
+			'-' => Some(Self::Remove),
+			'!' => Some(Self::Clear),
+			_ => None,
+		}
+	}
+
+	pub fn prefix(&self) -> &'static str {
+		match self {
+			Self::Set => "",
+			Self::Add => "+",
+			Self::AddDuplicate => ".",
+			Self::Remove => "-",
+			Self::Clear => "!",
+		}
+	}
+}
+
+/// A single `Key=Value` line of an ini file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IniEntry {
+	pub op: IniOp,
+	pub key: String,
+	pub value: String,
+}
+
+impl IniEntry {
+	fn parse(line: &str) -> Option<Self> {
+		let (key, value) = line.split_once('=')?;
+		let key = key.trim();
+		let mut chars = key.chars();
+		let (op, key) = match chars.next().and_then(IniOp::from_prefix) {
+			Some(op) => (op, chars.as_str().trim_start()),
+			None => (IniOp::Set, key),
+		};
+		if key.is_empty() {
+			return None;
+		}
+		let value = value.trim();
+		// Quoted values are unwrapped, as the engine does when reading them
+		let value = match value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+			true => value[1..value.len() - 1].replace("\\\"", "\""),
+			false => value.to_owned(),
+		};
+		Some(Self {
+			op,
+			key: key.to_owned(),
+			value,
+		})
+	}
+
+	fn matches(&self, key: &str, value: &str) -> bool {
+		self.key.eq_ignore_ascii_case(key) && self.value == value
+	}
+}
+
+impl std::fmt::Display for IniEntry {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}{}={}", self.op.prefix(), self.key, self.value)
+	}
+}
+
+#[derive(Clone, Debug)]
+enum LineKind {
+	/// Blank lines, comments, and anything else which the engine ignores.
+	Other,
+	Section(String),
+	Entry(IniEntry),
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+	/// The text of the line as it was read, so that unchanged lines are written back exactly.
+	raw: String,
+	kind: LineKind,
+}
+
+impl Line {
+	fn from_entry(entry: IniEntry) -> Self {
+		Self {
+			raw: entry.to_string(),
+			kind: LineKind::Entry(entry),
+		}
+	}
+
+	fn parse(raw: &str) -> Self {
+		let trimmed = raw.trim();
+		let kind = if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+			LineKind::Other
+		} else if let Some(name) = trimmed
+			.strip_prefix('[')
+			.and_then(|name| name.strip_suffix(']'))
+		{
+			LineKind::Section(name.to_owned())
+		} else {
+			match IniEntry::parse(trimmed) {
+				Some(entry) => LineKind::Entry(entry),
+				None => LineKind::Other,
+			}
+		};
+		Self {
+			raw: raw.to_owned(),
+			kind,
+		}
+	}
+}
+
+/// A single Unreal config file (e.g. `DefaultEngine.ini`).
+///
+/// The original text of every line is kept, so that writing the file back only changes the lines which were modified.
+#[derive(Clone, Debug, Default)]
+pub struct IniFile {
+	lines: Vec<Line>,
+	crlf: bool,
+	trailing_newline: bool,
+}
+
+impl IniFile {
+	pub fn parse(text: &str) -> Self {
+		let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+		let trailing_newline = text.ends_with('\n');
+		let body = text.strip_suffix('\n').unwrap_or(text);
+		let lines = match body.is_empty() {
+			true => Vec::new(),
+			false => body
+				.split('\n')
+				.map(|line| Line::parse(line.strip_suffix('\r').unwrap_or(line)))
+				.collect(),
+		};
+		Self {
+			lines,
+			crlf: text.contains("\r\n"),
+			trailing_newline,
+		}
+	}
+
+	pub async fn read(path: &Path) -> anyhow::Result<Self> {
+		let text = tokio::fs::read_to_string(path)
+			.await
+			.context(format!("read {:?}", path))?;
+		Ok(Self::parse(&text))
+	}
+
+	/// The names of all sections in the file, in the order they first appear.
+	pub fn section_names(&self) -> Vec<&str> {
+		let mut names: Vec<&str> = Vec::new();
+		for line in self.lines.iter() {
+			if let LineKind::Section(name) = &line.kind {
+				if !names
+					.iter()
+					.any(|existing| existing.eq_ignore_ascii_case(name))
+				{
+					names.push(name);
+				}
+			}
+		}
+		names
+	}
+
+	/// All entries in the file, paired with the name of the section they are in.
+	/// Entries before the first section header are ignored, as they are by the engine.
+	pub fn entries(&self) -> impl Iterator<Item = (&str, &IniEntry)> {
+		let mut section = None;
+		self.lines.iter().filter_map(move |line| match &line.kind {
+			LineKind::Section(name) => {
+				section = Some(name.as_str());
+				None
+			}
+			LineKind::Entry(entry) => Some((section?, entry)),
+			LineKind::Other => None,
+		})
+	}
+
+	/// The entries in the section, in file order. Section names and keys are case-insensitive.
+	pub fn section_entries(&self, section: &str) -> impl Iterator<Item = &IniEntry> {
+		let section = section.to_owned();
+		self.entries()
+			.filter(move |(name, _)| name.eq_ignore_ascii_case(&section))
+			.map(|(_, entry)| entry)
+	}
+
+	/// The value of the last plain `Key=Value` entry for the key in the section.
+	/// Use an [`IniHierarchy`](super::IniHierarchy) to take the array operators into account.
+	pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+		self.section_entries(section)
+			.filter(|entry| entry.op == IniOp::Set && entry.key.eq_ignore_ascii_case(key))
+			.last()
+			.map(|entry| entry.value.as_str())
+	}
+
+	/// Replaces all plain values of the key in the section with a single `Key=Value` entry.
+	/// The first existing entry is updated in place, otherwise the entry is added to the end of the section.
+	pub fn set(&mut self, section: &str, key: &str, value: &str) {
+		let entry = IniEntry {
+			op: IniOp::Set,
+			key: key.to_owned(),
+			value: value.to_owned(),
+		};
+		let existing = self.entry_indices(section, |existing| {
+			existing.op == IniOp::Set && existing.key.eq_ignore_ascii_case(key)
+		});
+		match existing.split_first() {
+			Some((first, rest)) => {
+				self.lines[*first] = Line::from_entry(entry);
+				self.remove_lines(rest);
+			}
+			None => self.push_entry(section, entry),
+		}
+	}
+
+	/// Removes every entry for the key in the section, regardless of its operator.
+	/// Returns true if any entries were removed.
+	pub fn unset(&mut self, section: &str, key: &str) -> bool {
+		let indices =
+			self.entry_indices(section, |existing| existing.key.eq_ignore_ascii_case(key));
+		self.remove_lines(&indices);
+		!indices.is_empty()
+	}
+
+	/// Adds a `+Key=Value` entry, unless the file already adds the value.
+	/// Any entry in this file which removes the value is dropped.
+	/// Returns true if the file changed.
+	pub fn add_to_array(&mut self, section: &str, key: &str, value: &str) -> bool {
+		let removals = self.entry_indices(section, |existing| {
+			existing.op == IniOp::Remove && existing.matches(key, value)
+		});
+		self.remove_lines(&removals);
+		let already_added = !self
+			.entry_indices(section, |existing| {
+				matches!(existing.op, IniOp::Add | IniOp::AddDuplicate)
+					&& existing.matches(key, value)
+			})
+			.is_empty();
+		if !already_added {
+			self.push_entry(
+				section,
+				IniEntry {
+					op: IniOp::Add,
+					key: key.to_owned(),
+					value: value.to_owned(),
+				},
+			);
+		}
+		!removals.is_empty() || !already_added
+	}
+
+	/// Drops any entries in this file which add the value to the array.
+	/// Returns true if any entries were removed.
+	pub fn remove_array_additions(&mut self, section: &str, key: &str, value: &str) -> bool {
+		let additions = self.entry_indices(section, |existing| {
+			existing.op != IniOp::Remove
+				&& existing.op != IniOp::Clear
+				&& existing.matches(key, value)
+		});
+		self.remove_lines(&additions);
+		!additions.is_empty()
+	}
+
+	/// Adds the entry after the last entry of the section, creating the section at the end of the file if needed.
+	pub fn push_entry(&mut self, section: &str, entry: IniEntry) {
+		let mut current_section = None;
+		let mut insert_at = None;
+		for (idx, line) in self.lines.iter().enumerate() {
+			match &line.kind {
+				LineKind::Section(name) => current_section = Some(name.as_str()),
+				LineKind::Entry(_)
+					if current_section.is_some_and(|name| name.eq_ignore_ascii_case(section)) =>
+				{
+					insert_at = Some(idx + 1);
+				}
+				_ => {}
+			}
+		}
+		// A section header which has no entries yet
+		let insert_at = insert_at.or_else(|| {
+			self.lines
+				.iter()
+				.position(
+					|line| matches!(&line.kind, LineKind::Section(name) if name.eq_ignore_ascii_case(section)),
+				)
+				.map(|idx| idx + 1)
+		});
+		match insert_at {
+			Some(idx) => self.lines.insert(idx, Line::from_entry(entry)),
+			None => {
+				if self
+					.lines
+					.last()
+					.is_some_and(|line| !line.raw.trim().is_empty())
+				{
+					self.lines.push(Line::parse(""));
+				}
+				self.lines.push(Line::parse(&format!("[{section}]")));
+				self.lines.push(Line::from_entry(entry));
+				if self.lines.len() == 2 {
+					// A new file, which the engine writes with a trailing newline
+					self.trailing_newline = true;
+				}
+			}
+		}
+	}
+
+	fn entry_indices(&self, section: &str, predicate: impl Fn(&IniEntry) -> bool) -> Vec<usize> {
+		let mut current_section = None;
+		let mut indices = Vec::new();
+		for (idx, line) in self.lines.iter().enumerate() {
+			match &line.kind {
+				LineKind::Section(name) => current_section = Some(name.as_str()),
+				LineKind::Entry(entry) => {
+					let in_section =
+						current_section.is_some_and(|name| name.eq_ignore_ascii_case(section));
+					if in_section && predicate(entry) {
+						indices.push(idx);
+					}
+				}
+				LineKind::Other => {}
+			}
+		}
+		indices
+	}
+
+	fn remove_lines(&mut self, indices: &[usize]) {
+		for idx in indices.iter().rev() {
+			self.lines.remove(*idx);
+		}
+	}
+}
+
+impl std::fmt::Display for IniFile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let newline = match self.crlf {
+			true => "\r\n",
+			false => "\n",
+		};
+		for (idx, line) in self.lines.iter().enumerate() {
+			if idx > 0 {
+				f.write_str(newline)?;
+			}
+			f.write_str(&line.raw)?;
+		}
+		if self.trailing_newline && !self.lines.is_empty() {
+			f.write_str(newline)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_entry_operators() {
+		let file = IniFile::parse(
+			"[Section]\nKey=1\n+Key=2\n.Key=3\n-Key=4\n!Key=ClearArray\nQuoted=\"a \\\"b\\\"\"\n",
+		);
+		let entries = file
+			.section_entries("section")
+			.map(|entry| (entry.op, entry.value.as_str()))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			entries,
+			[
+				(IniOp::Set, "1"),
+				(IniOp::Add, "2"),
+				(IniOp::AddDuplicate, "3"),
+				(IniOp::Remove, "4"),
+				(IniOp::Clear, "ClearArray"),
+				(IniOp::Set, "a \"b\""),
+			]
+		);
+		assert_eq!(file.get("Section", "Key"), Some("1"));
+	}
+
+	#[test]
+	fn ignores_comments_and_entries_before_sections() {
+		let file = IniFile::parse("Orphan=1\n; Key=2\n[Section]\n# Key=3\nKey=4\n=5\n");
+		let entries = file
+			.entries()
+			.map(|(section, entry)| (section, entry.to_string()))
+			.collect::<Vec<_>>();
+		assert_eq!(entries, [("Section", "Key=4".to_owned())]);
+	}
+
+	#[test]
+	fn edits_keep_other_lines() {
+		let text = "[A]\r\n; comment\r\nKey = 1\r\nKey=2\r\n\r\n[B]\r\n+Arr=x\r\n";
+		let mut file = IniFile::parse(text);
+		assert_eq!(file.to_string(), text);
+		file.set("A", "Key", "3");
+		assert!(file.add_to_array("B", "Arr", "y"));
+		assert!(!file.add_to_array("B", "Arr", "x"));
+		assert_eq!(
+			file.to_string(),
+			"[A]\r\n; comment\r\nKey=3\r\n\r\n[B]\r\n+Arr=x\r\n+Arr=y\r\n"
+		);
+	}
+}
@@ -0,0 +1,321 @@
+use super::{IniFile, IniOp};
+use crate::types::Platform;
+use std::path::PathBuf;
+
+/// The directories which the layers of a config hierarchy are read from.
+#[derive(Clone, Debug)]
+pub struct IniLocations {
+	/// The engine directory (containing `Config/Base.ini`).
+	pub engine_dir: PathBuf,
+	/// The project directory (containing `Config/Default*.ini`).
+	pub project_root: PathBuf,
+	/// The directory that the editor saves user overrides to (e.g. `Saved/Config/WindowsEditor`).
+	pub saved_config_dir: Option<PathBuf>,
+}
+
+impl IniLocations {
+	/// The paths of every file which contributes to the category (e.g. `Engine` or `Game`),
+	/// from the lowest to the highest priority. The files do not need to exist.
+	pub fn layers(&self, category: &str, platform: Option<Platform>) -> Vec<PathBuf> {
+		let engine_config = self.engine_dir.join("Config");
+		let mut layers = vec![
+			engine_config.join("Base.ini"),
+			engine_config.join(format!("Base{category}.ini")),
+		];
+		if let Some(platform) = platform {
+			let platform = platform.ini_name();
+			layers.push(engine_config.join(format!("{platform}/Base{platform}{category}.ini")));
+		}
+		layers.push(self.project_file(category, None));
+		if let Some(platform) = platform {
+			let platform_name = platform.ini_name();
+			layers
+				.push(engine_config.join(format!("{platform_name}/{platform_name}{category}.ini")));
+			layers.push(self.project_file(category, Some(platform)));
+		}
+		if let Some(saved_config_dir) = &self.saved_config_dir {
+			layers.push(saved_config_dir.join(format!("{category}.ini")));
+		}
+		layers
+	}
+
+	/// The project's file for the category: `Config/Default{Category}.ini`,
+	/// or `Config/{Platform}/{Platform}{Category}.ini` for a platform.
+	pub fn project_file(&self, category: &str, platform: Option<Platform>) -> PathBuf {
+		let project_config = self.project_root.join("Config");
+		match platform {
+			Some(platform) => {
+				let platform = platform.ini_name();
+				project_config.join(format!("{platform}/{platform}{category}.ini"))
+			}
+			None => project_config.join(format!("Default{category}.ini")),
+		}
+	}
+}
+
+/// The values of a section after all layers of a hierarchy have been applied.
+#[derive(Clone, Debug, Default)]
+pub struct IniSection {
+	name: String,
+	values: Vec<(String, String)>,
+}
+
+impl IniSection {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// The last value of the key. Keys are case-insensitive.
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.get_all(key).last().copied()
+	}
+
+	/// All values of an array key, in order.
+	pub fn get_all(&self, key: &str) -> Vec<&str> {
+		self.values
+			.iter()
+			.filter(|(existing, _)| existing.eq_ignore_ascii_case(key))
+			.map(|(_, value)| value.as_str())
+			.collect()
+	}
+
+	/// Every key and value in the section, in the order they were added.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.values
+			.iter()
+			.map(|(key, value)| (key.as_str(), value.as_str()))
+	}
+
+	fn apply(&mut self, op: IniOp, key: &str, value: &str, first_in_layer: bool) {
+		let matches_key = |existing: &String| existing.eq_ignore_ascii_case(key);
+		match op {
+			IniOp::Set => {
+				// A plain assignment replaces values from earlier layers,
+				// but repeated assignments in the same file build up an array.
+				if first_in_layer {
+					self.values.retain(|(existing, _)| !matches_key(existing));
+				}
+				self.values.push((key.to_owned(), value.to_owned()));
+			}
+			IniOp::Add => {
+				let exists = self.values.iter().any(|(existing, existing_value)| {
+					matches_key(existing) && existing_value == value
+				});
+				if !exists {
+					self.values.push((key.to_owned(), value.to_owned()));
+				}
+			}
+			IniOp::AddDuplicate => self.values.push((key.to_owned(), value.to_owned())),
+			IniOp::Remove => {
+				// Like the engine, only the first matching value is removed
+				let position = self.values.iter().position(|(existing, existing_value)| {
+					matches_key(existing) && existing_value == value
+				});
+				if let Some(idx) = position {
+					self.values.remove(idx);
+				}
+			}
+			IniOp::Clear => self.values.retain(|(existing, _)| !matches_key(existing)),
+		}
+	}
+}
+
+/// The merged view of a config category (e.g. `Engine`), matching what the engine sees at runtime.
+#[derive(Clone, Debug, Default)]
+pub struct IniHierarchy {
+	layers: Vec<PathBuf>,
+	sections: Vec<IniSection>,
+}
+
+impl IniHierarchy {
+	/// Reads and merges every layer of the category which exists on disk.
+	pub async fn load(
+		locations: &IniLocations,
+		category: &str,
+		platform: Option<Platform>,
+	) -> anyhow::Result<Self> {
+		let mut hierarchy = Self::default();
+		for path in locations.layers(category, platform).into_iter() {
+			if !path.exists() {
+				continue;
+			}
+			let file = IniFile::read(&path).await?;
+			hierarchy.apply(&file);
+			hierarchy.layers.push(path);
+		}
+		Ok(hierarchy)
+	}
+
+	/// Merges the file on top of all previously applied layers.
+	pub fn apply(&mut self, file: &IniFile) {
+		let mut assigned_in_layer: Vec<(String, String)> = Vec::new();
+		for (section_name, entry) in file.entries() {
+			let first_in_layer = match entry.op {
+				IniOp::Set => {
+					let seen = assigned_in_layer.iter().any(|(section, key)| {
+						section.eq_ignore_ascii_case(section_name)
+							&& key.eq_ignore_ascii_case(&entry.key)
+					});
+					if !seen {
+						assigned_in_layer.push((section_name.to_owned(), entry.key.clone()));
+					}
+					!seen
+				}
+				_ => false,
+			};
+			self.section_mut(section_name).apply(
+				entry.op,
+				&entry.key,
+				&entry.value,
+				first_in_layer,
+			);
+		}
+	}
+
+	/// The files which were merged into the hierarchy, from the lowest to the highest priority.
+	pub fn layers(&self) -> &Vec<PathBuf> {
+		&self.layers
+	}
+
+	/// The section with the name, which is case-insensitive.
+	pub fn section(&self, name: &str) -> Option<&IniSection> {
+		self.sections
+			.iter()
+			.find(|section| section.name.eq_ignore_ascii_case(name))
+	}
+
+	pub fn sections(&self) -> &Vec<IniSection> {
+		&self.sections
+	}
+
+	/// The final value of the key in the section.
+	pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+		self.section(section)?.get(key)
+	}
+
+	/// All values of the array key in the section.
+	pub fn get_all(&self, section: &str, key: &str) -> Vec<&str> {
+		match self.section(section) {
+			Some(section) => section.get_all(key),
+			None => Vec::new(),
+		}
+	}
+
+	fn section_mut(&mut self, name: &str) -> &mut IniSection {
+		let idx = match self
+			.sections
+			.iter()
+			.position(|section| section.name.eq_ignore_ascii_case(name))
+		{
+			Some(idx) => idx,
+			None => {
+				self.sections.push(IniSection {
+					name: name.to_owned(),
+					values: Vec::new(),
+				});
+				self.sections.len() - 1
+			}
+		};
+		&mut self.sections[idx]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn merge(layers: &[&str]) -> IniHierarchy {
+		let mut hierarchy = IniHierarchy::default();
+		for text in layers.iter() {
+			hierarchy.apply(&IniFile::parse(text));
+		}
+		hierarchy
+	}
+
+	#[test]
+	fn layers_from_lowest_to_highest_priority() {
+		let locations = IniLocations {
+			engine_dir: PathBuf::from("/engine"),
+			project_root: PathBuf::from("/project"),
+			saved_config_dir: Some(PathBuf::from("/project/Saved/Config/LinuxEditor")),
+		};
+		assert_eq!(
+			locations.layers("Engine", Some(Platform::Linux)),
+			[
+				"/engine/Config/Base.ini",
+				"/engine/Config/BaseEngine.ini",
+				"/engine/Config/Linux/BaseLinuxEngine.ini",
+				"/project/Config/DefaultEngine.ini",
+				"/engine/Config/Linux/LinuxEngine.ini",
+				"/project/Config/Linux/LinuxEngine.ini",
+				"/project/Saved/Config/LinuxEditor/Engine.ini",
+			]
+			.map(PathBuf::from)
+		);
+		assert_eq!(
+			locations.layers("Game", None),
+			[
+				"/engine/Config/Base.ini",
+				"/engine/Config/BaseGame.ini",
+				"/project/Config/DefaultGame.ini",
+				"/project/Saved/Config/LinuxEditor/Game.ini",
+			]
+			.map(PathBuf::from)
+		);
+	}
+
+	#[test]
+	fn later_layers_replace_plain_values() {
+		let hierarchy = merge(&[
+			"[/Script/Engine.Engine]\nGameName=Base\nOther=1\n",
+			"[/script/engine.engine]\ngamename=Project\n",
+		]);
+		assert_eq!(
+			hierarchy.get("/Script/Engine.Engine", "GameName"),
+			Some("Project")
+		);
+		assert_eq!(
+			hierarchy.get_all("/Script/Engine.Engine", "GameName"),
+			["Project"]
+		);
+		assert_eq!(hierarchy.get("/Script/Engine.Engine", "Other"), Some("1"));
+	}
+
+	#[test]
+	fn repeated_plain_values_in_a_layer_build_an_array() {
+		let hierarchy = merge(&["[Section]\nKey=Base\n", "[Section]\nKey=A\nKey=B\nKey=A\n"]);
+		assert_eq!(hierarchy.get_all("Section", "Key"), ["A", "B", "A"]);
+		assert_eq!(hierarchy.get("Section", "Key"), Some("A"));
+	}
+
+	#[test]
+	fn add_skips_existing_values() {
+		let hierarchy = merge(&["[Section]\n+Key=A\n", "[Section]\n+Key=A\n+Key=B\n"]);
+		assert_eq!(hierarchy.get_all("Section", "Key"), ["A", "B"]);
+	}
+
+	#[test]
+	fn add_duplicate_keeps_existing_values() {
+		let hierarchy = merge(&["[Section]\n+Key=A\n", "[Section]\n.Key=A\n.Key=B\n"]);
+		assert_eq!(hierarchy.get_all("Section", "Key"), ["A", "A", "B"]);
+	}
+
+	#[test]
+	fn remove_drops_a_single_matching_value() {
+		let hierarchy = merge(&[
+			"[Section]\n.Key=A\n.Key=B\n.Key=A\n",
+			"[Section]\n-Key=A\n-Key=Missing\n",
+		]);
+		assert_eq!(hierarchy.get_all("Section", "Key"), ["B", "A"]);
+	}
+
+	#[test]
+	fn clear_drops_every_value() {
+		let hierarchy = merge(&[
+			"[Section]\n+Key=A\n+Key=B\nOther=1\n",
+			"[Section]\n!Key=ClearArray\n+Key=C\n",
+		]);
+		assert_eq!(hierarchy.get_all("Section", "Key"), ["C"]);
+		assert_eq!(hierarchy.get("Section", "Other"), Some("1"));
+	}
+}
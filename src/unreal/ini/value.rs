@@ -0,0 +1,376 @@
+/// A value written in Unreal's text format for structs and arrays,
+/// e.g. `(Name="dm",GameMode="/Game/Modes/BP_DM.BP_DM_C")`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IniValue {
+	/// `(Key=Value,...)`, with fields in the order they were written.
+	Struct(Vec<(String, IniValue)>),
+	/// `(Value,...)`
+	Array(Vec<IniValue>),
+	/// A quoted string, with its escape sequences resolved.
+	String(String),
+	/// Any unquoted text (e.g. numbers, enum values, object paths or `NSLOCTEXT(...)`).
+	Literal(String),
+}
+
+impl IniValue {
+	pub fn parse(text: &str) -> Result<Self, ParseIniValueError> {
+		let mut parser = Parser {
+			chars: text.chars().collect(),
+			pos: 0,
+		};
+		let value = parser.value()?;
+		parser.skip_whitespace();
+		if parser.pos < parser.chars.len() {
+			return Err(parser.error("unexpected text after the value"));
+		}
+		Ok(value)
+	}
+
+	/// The text of a string or literal value.
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Self::String(value) | Self::Literal(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	/// The items of an array value. An empty struct `()` is also treated as an empty array.
+	pub fn as_array(&self) -> Option<&[IniValue]> {
+		match self {
+			Self::Array(items) => Some(items),
+			Self::Struct(fields) if fields.is_empty() => Some(&[]),
+			_ => None,
+		}
+	}
+
+	/// The value of a struct's field. Field names are case-insensitive.
+	pub fn field(&self, name: &str) -> Option<&IniValue> {
+		match self {
+			Self::Struct(fields) => fields
+				.iter()
+				.find(|(field, _)| field.eq_ignore_ascii_case(name))
+				.map(|(_, value)| value),
+			_ => None,
+		}
+	}
+
+	/// The text of a struct's string or literal field.
+	pub fn field_str(&self, name: &str) -> Option<&str> {
+		self.field(name)?.as_str()
+	}
+}
+
+impl std::str::FromStr for IniValue {
+	type Err = ParseIniValueError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::parse(s)
+	}
+}
+
+impl std::fmt::Display for IniValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Struct(fields) => {
+				write!(f, "(")?;
+				for (idx, (name, value)) in fields.iter().enumerate() {
+					if idx > 0 {
+						write!(f, ",")?;
+					}
+					write!(f, "{name}={value}")?;
+				}
+				write!(f, ")")
+			}
+			Self::Array(items) => {
+				write!(f, "(")?;
+				for (idx, value) in items.iter().enumerate() {
+					if idx > 0 {
+						write!(f, ",")?;
+					}
+					write!(f, "{value}")?;
+				}
+				write!(f, ")")
+			}
+			Self::String(value) => {
+				let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+				write!(f, "\"{escaped}\"")
+			}
+			Self::Literal(value) => write!(f, "{value}"),
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct ParseIniValueError {
+	/// The character offset in the text where parsing failed.
+	pub position: usize,
+	pub message: &'static str,
+}
+impl std::fmt::Display for ParseIniValueError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"Invalid ini value at character {}: {}",
+			self.position, self.message
+		)
+	}
+}
+
+struct Parser {
+	chars: Vec<char>,
+	pos: usize,
+}
+
+impl Parser {
+	fn error(&self, message: &'static str) -> ParseIniValueError {
+		ParseIniValueError {
+			position: self.pos,
+			message,
+		}
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.chars.get(self.pos).copied()
+	}
+
+	fn skip_whitespace(&mut self) {
+		while self.peek().is_some_and(char::is_whitespace) {
+			self.pos += 1;
+		}
+	}
+
+	fn value(&mut self) -> Result<IniValue, ParseIniValueError> {
+		self.skip_whitespace();
+		match self.peek() {
+			Some('(') => self.group(),
+			Some('"') => Ok(IniValue::String(self.string()?)),
+			_ => Ok(IniValue::Literal(self.literal()?)),
+		}
+	}
+
+	/// Parses a parenthesized struct or array, deciding which it is by whether the first item is `Key=Value`.
+	fn group(&mut self) -> Result<IniValue, ParseIniValueError> {
+		self.pos += 1;
+		self.skip_whitespace();
+		if self.peek() == Some(')') {
+			self.pos += 1;
+			return Ok(IniValue::Array(Vec::new()));
+		}
+		let is_struct = self.field_name().is_some();
+		let mut fields = Vec::new();
+		let mut items = Vec::new();
+		loop {
+			self.skip_whitespace();
+			if is_struct {
+				let Some((name, end)) = self.field_name() else {
+					return Err(self.error("expected a field name"));
+				};
+				self.pos = end;
+				fields.push((name, self.value()?));
+			} else {
+				items.push(self.value()?);
+			}
+			self.skip_whitespace();
+			match self.peek() {
+				Some(',') => self.pos += 1,
+				Some(')') => {
+					self.pos += 1;
+					break;
+				}
+				Some(_) => return Err(self.error("expected ',' or ')'")),
+				None => return Err(self.error("missing ')'")),
+			}
+		}
+		Ok(match is_struct {
+			true => IniValue::Struct(fields),
+			false => IniValue::Array(items),
+		})
+	}
+
+	/// Reads a `Name=` field prefix at the current position (without consuming it),
+	/// returning the name and the position after the `=`.
+	fn field_name(&self) -> Option<(String, usize)> {
+		let mut end = self.pos;
+		while self
+			.chars
+			.get(end)
+			.is_some_and(|c| c.is_alphanumeric() || *c == '_')
+		{
+			end += 1;
+		}
+		if end == self.pos {
+			return None;
+		}
+		let name = self.chars[self.pos..end].iter().collect::<String>();
+		while self.chars.get(end).is_some_and(|c| c.is_whitespace()) {
+			end += 1;
+		}
+		match self.chars.get(end) {
+			Some('=') => Some((name, end + 1)),
+			_ => None,
+		}
+	}
+
+	fn string(&mut self) -> Result<String, ParseIniValueError> {
+		self.pos += 1;
+		let mut value = String::new();
+		loop {
+			match self.peek() {
+				Some('"') => {
+					self.pos += 1;
+					return Ok(value);
+				}
+				Some('\\') => {
+					self.pos += 1;
+					match self.peek() {
+						Some('n') => value.push('\n'),
+						Some('t') => value.push('\t'),
+						Some(c) => value.push(c),
+						None => return Err(self.error("unterminated escape sequence")),
+					}
+					self.pos += 1;
+				}
+				Some(c) => {
+					value.push(c);
+					self.pos += 1;
+				}
+				None => return Err(self.error("missing closing '\"'")),
+			}
+		}
+	}
+
+	/// Reads unquoted text up to the next top-level `,` or `)`.
+	/// Parentheses and quotes inside the literal (e.g. `NSLOCTEXT("", "", "")`) are kept as-is.
+	fn literal(&mut self) -> Result<String, ParseIniValueError> {
+		let start = self.pos;
+		let mut depth = 0usize;
+		let mut in_quotes = false;
+		while let Some(c) = self.peek() {
+			match c {
+				'"' => in_quotes = !in_quotes,
+				'\\' if in_quotes => self.pos += 1,
+				'(' if !in_quotes => depth += 1,
+				')' if !in_quotes && depth > 0 => depth -= 1,
+				',' | ')' if !in_quotes && depth == 0 => break,
+				_ => {}
+			}
+			self.pos += 1;
+		}
+		if in_quotes {
+			return Err(self.error("missing closing '\"'"));
+		}
+		if depth > 0 {
+			return Err(self.error("missing ')'"));
+		}
+		let end = self.pos.min(self.chars.len());
+		Ok(self.chars[start..end]
+			.iter()
+			.collect::<String>()
+			.trim()
+			.to_owned())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn literal(text: &str) -> IniValue {
+		IniValue::Literal(text.to_owned())
+	}
+
+	fn string(text: &str) -> IniValue {
+		IniValue::String(text.to_owned())
+	}
+
+	#[test]
+	fn parses_nested_structs() {
+		let value = IniValue::parse(
+			r#"(Name="dm", Mode=(Path=/Game/BP_DM.BP_DM_C, Teams=(Red,Blue)), Max = 8)"#,
+		)
+		.unwrap();
+		assert_eq!(
+			value,
+			IniValue::Struct(vec![
+				("Name".to_owned(), string("dm")),
+				(
+					"Mode".to_owned(),
+					IniValue::Struct(vec![
+						("Path".to_owned(), literal("/Game/BP_DM.BP_DM_C")),
+						(
+							"Teams".to_owned(),
+							IniValue::Array(vec![literal("Red"), literal("Blue")])
+						),
+					])
+				),
+				("Max".to_owned(), literal("8")),
+			])
+		);
+		assert_eq!(
+			value.field("mode").and_then(|mode| mode.field_str("path")),
+			Some("/Game/BP_DM.BP_DM_C")
+		);
+	}
+
+	#[test]
+	fn quoted_strings_keep_commas_and_parentheses() {
+		let value = IniValue::parse(r#"("a, b", "(c)", "say \"hi\"\n", "back\\slash")"#).unwrap();
+		assert_eq!(
+			value,
+			IniValue::Array(vec![
+				string("a, b"),
+				string("(c)"),
+				string("say \"hi\"\n"),
+				string("back\\slash"),
+			])
+		);
+		let value = IniValue::parse(r#"(Text=NSLOCTEXT("", "Key", "Hello, (world)"))"#).unwrap();
+		assert_eq!(
+			value.field_str("Text"),
+			Some(r#"NSLOCTEXT("", "Key", "Hello, (world)")"#)
+		);
+	}
+
+	#[test]
+	fn parses_empty_arrays() {
+		assert_eq!(IniValue::parse("()").unwrap(), IniValue::Array(Vec::new()));
+		assert_eq!(
+			IniValue::parse(" (  ) ").unwrap(),
+			IniValue::Array(Vec::new())
+		);
+		let value = IniValue::parse("(Maps=(),Name=x)").unwrap();
+		assert_eq!(
+			value.field("Maps").and_then(IniValue::as_array),
+			Some(&[][..])
+		);
+	}
+
+	#[test]
+	fn display_round_trips() {
+		for text in [r#"(Name="a, \"b\"",Items=(1,2,()))"#, "Literal", "()"] {
+			assert_eq!(IniValue::parse(text).unwrap().to_string(), text);
+		}
+	}
+
+	#[test]
+	fn malformed_values_are_errors() {
+		for text in [
+			"(",
+			"(A=1",
+			"(A=1,",
+			"(1,2",
+			"(A=1,2)",
+			"(A=\"1\" B=2)",
+			"\"unterminated",
+			"\"escape\\",
+			"(\"a\"b)",
+			"NSLOCTEXT(\"a\"",
+			"Literal)",
+			"(A=1))",
+			"Quoted\"",
+			"\"a\\",
+		] {
+			assert!(IniValue::parse(text).is_err(), "{text:?} should not parse");
+		}
+	}
+}
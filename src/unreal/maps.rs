@@ -0,0 +1,84 @@
+use super::DiscoveredPlugin;
+use std::path::{Path, PathBuf};
+
+/// A level (`.umap`) in the project's or a plugin's content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MapAsset {
+	/// The long package name of the map (e.g. `/Game/Maps/Arena` or `/MyPlugin/Maps/Arena`).
+	pub package: String,
+	/// The `.umap` file, if the map was found on disk.
+	pub file: Option<PathBuf>,
+	/// If the map is listed in the packaging settings' `MapsToCook`.
+	pub cooked: bool,
+}
+
+impl MapAsset {
+	/// The short name of the map (e.g. `Arena`).
+	pub fn name(&self) -> &str {
+		self.package
+			.rsplit('/')
+			.next()
+			.unwrap_or(self.package.as_str())
+	}
+
+	/// The object path used to open the map, which by unreal convention is suffixed with `.{name}`
+	/// (e.g. `/Game/Maps/Arena.Arena`).
+	pub fn object_path(&self) -> String {
+		format!("{}.{}", self.package, self.name())
+	}
+}
+
+/// Finds every `.umap` in the project's `Content` directory and the content of plugins which can contain it,
+/// sorted by package name.
+pub fn discover_maps(project_root: &Path, plugins: &[DiscoveredPlugin]) -> Vec<MapAsset> {
+	let mut mounts = vec![("Game".to_owned(), project_root.join("Content"))];
+	for plugin in plugins.iter() {
+		if plugin.descriptor.can_contain_content {
+			mounts.push((plugin.name.clone(), plugin.dir().join("Content")));
+		}
+	}
+
+	let mut maps = Vec::new();
+	for (mount, content_dir) in mounts.into_iter() {
+		for file in find_files_with_extension(&content_dir, "umap") {
+			let Ok(relative) = file
+				.with_extension("")
+				.strip_prefix(&content_dir)
+				.map(ToOwned::to_owned)
+			else {
+				continue;
+			};
+			let relative = relative
+				.components()
+				.map(|component| component.as_os_str().to_string_lossy())
+				.collect::<Vec<_>>()
+				.join("/");
+			maps.push(MapAsset {
+				package: format!("/{mount}/{relative}"),
+				file: Some(file),
+				cooked: false,
+			});
+		}
+	}
+	maps.sort_by(|a, b| a.package.cmp(&b.package));
+	maps
+}
+
+fn find_files_with_extension(root: &Path, extension: &str) -> Vec<PathBuf> {
+	let mut files = Vec::new();
+	let mut pending = vec![root.to_owned()];
+	while let Some(dir) = pending.pop() {
+		let Ok(entries) = std::fs::read_dir(&dir) else {
+			continue;
+		};
+		for entry in entries.flatten() {
+			let path = entry.path();
+			if path.is_dir() {
+				pending.push(path);
+			} else if path.extension().is_some_and(|ext| ext == extension) {
+				files.push(path);
+			}
+		}
+	}
+	files
+}
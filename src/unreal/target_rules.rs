@@ -0,0 +1,97 @@
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+	path::{Path, PathBuf},
+	sync::LazyLock,
+};
+
+/// The kind of binary a target builds, as declared by `Type = TargetType.X` in its `.Target.cs` file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TargetType {
+	Game,
+	Client,
+	Server,
+	Editor,
+	Program,
+}
+
+impl TargetType {
+	fn parse(value: &str) -> Option<Self> {
+		match value {
+			"Game" => Some(Self::Game),
+			"Client" => Some(Self::Client),
+			"Server" => Some(Self::Server),
+			"Editor" => Some(Self::Editor),
+			"Program" => Some(Self::Program),
+			_ => None,
+		}
+	}
+}
+
+impl std::fmt::Display for TargetType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::Debug::fmt(self, f)
+	}
+}
+
+/// A build target declared by a `Source/{Name}.Target.cs` file.
+#[derive(Clone, Debug)]
+pub struct TargetRules {
+	/// The name passed to the build tool (the rules class name without the `Target` suffix).
+	pub name: String,
+	pub target_type: TargetType,
+	pub path: PathBuf,
+}
+
+impl TargetRules {
+	/// Extracts the target name and type from the C# source of a `.Target.cs` file.
+	/// Returns None if the source does not declare a `TargetRules` class.
+	pub fn parse(path: &Path, source: &str) -> Option<Self> {
+		static LINE_COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"//[^\n]*").unwrap());
+		static CLASS: LazyLock<Regex> =
+			LazyLock::new(|| Regex::new(r"class\s+(\w+?)Target\s*:\s*TargetRules").unwrap());
+		static TARGET_TYPE: LazyLock<Regex> =
+			LazyLock::new(|| Regex::new(r"\bType\s*=\s*TargetType\.(\w+)").unwrap());
+
+		// Drop line comments so that commented-out assignments are not matched
+		let source = LINE_COMMENT.replace_all(source, "");
+		let name = CLASS.captures(&source)?.get(1)?.as_str().to_owned();
+		let target_type = TARGET_TYPE
+			.captures(&source)
+			.and_then(|captures| TargetType::parse(captures.get(1)?.as_str()))
+			// The build tool assumes a game target when the rules do not set a type
+			.unwrap_or(TargetType::Game);
+		Some(Self {
+			name,
+			target_type,
+			path: path.to_owned(),
+		})
+	}
+
+	pub async fn read(path: &Path) -> anyhow::Result<Option<Self>> {
+		let source = tokio::fs::read_to_string(path)
+			.await
+			.context(format!("read {:?}", path))?;
+		Ok(Self::parse(path, &source))
+	}
+}
+
+/// Finds and parses every `Source/*.Target.cs` file in the project, sorted by target name.
+/// Files which cannot be read or do not declare a target are skipped.
+pub async fn discover_targets(project_root: &Path) -> Vec<TargetRules> {
+	let pattern = project_root.join("Source/*.Target.cs");
+	let Ok(paths) = glob::glob(&pattern.to_string_lossy()) else {
+		return Vec::new();
+	};
+	let mut targets = Vec::new();
+	for path in paths.flatten() {
+		match TargetRules::read(&path).await {
+			Ok(Some(target)) => targets.push(target),
+			Ok(None) => tracing::warn!("No TargetRules class found in {:?}", path),
+			Err(err) => tracing::warn!("{err:#}"),
+		}
+	}
+	targets.sort_by(|a, b| a.name.cmp(&b.name));
+	targets
+}
@@ -0,0 +1,129 @@
+use super::{Module, Plugin, UProject};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// The contents of a `.uplugin` descriptor.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct UPlugin {
+	#[serde(rename = "FileVersion", default)]
+	pub file_version: u32,
+	#[serde(rename = "Version", default)]
+	pub version: u32,
+	#[serde(rename = "VersionName", default)]
+	pub version_name: String,
+	#[serde(rename = "FriendlyName", default)]
+	pub friendly_name: String,
+	#[serde(rename = "Description", skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	#[serde(rename = "Category", skip_serializing_if = "Option::is_none")]
+	pub category: Option<String>,
+	#[serde(rename = "CreatedBy", skip_serializing_if = "Option::is_none")]
+	pub created_by: Option<String>,
+	/// If the plugin is enabled for projects which do not reference it.
+	#[serde(rename = "EnabledByDefault", skip_serializing_if = "Option::is_none")]
+	pub enabled_by_default: Option<bool>,
+	#[serde(rename = "CanContainContent", default)]
+	pub can_contain_content: bool,
+	#[serde(rename = "Installed", default)]
+	pub installed: bool,
+	#[serde(rename = "Modules", default, skip_serializing_if = "Vec::is_empty")]
+	pub modules: Vec<Module>,
+	/// Other plugins which this plugin depends on.
+	#[serde(rename = "Plugins", default, skip_serializing_if = "Vec::is_empty")]
+	pub plugins: Vec<Plugin>,
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
+}
+
+impl UPlugin {
+	pub async fn read(path: &Path) -> anyhow::Result<Self> {
+		let json = tokio::fs::read_to_string(path)
+			.await
+			.context(format!("read {:?}", path))?;
+		Ok(serde_json::from_str::<Self>(&json)?)
+	}
+}
+
+/// A plugin whose descriptor was found on disk.
+#[derive(Clone, Debug)]
+pub struct DiscoveredPlugin {
+	/// The name that projects and other plugins refer to this plugin by (the descriptor's file stem),
+	/// which may differ from the name of the directory it is in.
+	pub name: String,
+	/// The path to the `.uplugin` file.
+	pub descriptor_path: PathBuf,
+	pub descriptor: UPlugin,
+}
+
+impl DiscoveredPlugin {
+	/// The root directory of the plugin, which contains its descriptor and `Binaries`.
+	pub fn dir(&self) -> &Path {
+		self.descriptor_path.parent().unwrap()
+	}
+}
+
+/// Finds and reads every plugin in the project's `Plugins` directory and its `AdditionalPluginDirectories`.
+/// Descriptors which cannot be read are skipped.
+pub async fn discover_project_plugins(
+	project_root: &Path,
+	project: &UProject,
+) -> Vec<DiscoveredPlugin> {
+	let mut roots = vec![project_root.join("Plugins")];
+	// Relative directories are resolved against the project root, absolute ones are left as-is.
+	roots.extend(
+		project
+			.additional_plugin_directories
+			.iter()
+			.map(|dir| project_root.join(dir)),
+	);
+
+	let mut plugins = Vec::new();
+	for root in roots.into_iter() {
+		for descriptor_path in find_plugin_descriptors(&root) {
+			let Some(name) = descriptor_path.file_stem().and_then(|stem| stem.to_str()) else {
+				continue;
+			};
+			let name = name.to_owned();
+			match UPlugin::read(&descriptor_path).await {
+				Ok(descriptor) => plugins.push(DiscoveredPlugin {
+					name,
+					descriptor_path,
+					descriptor,
+				}),
+				Err(err) => tracing::warn!("Failed to read plugin {name:?}: {err:#}"),
+			}
+		}
+	}
+	plugins
+}
+
+/// Finds the `.uplugin` descriptors under a directory (e.g. `{Project}/Plugins` or `{Engine}/Plugins`).
+///
+/// Like the engine, directories are not searched any deeper once they contain a descriptor.
+pub fn find_plugin_descriptors(root: &Path) -> Vec<PathBuf> {
+	let mut descriptors = Vec::new();
+	let mut pending = vec![root.to_owned()];
+	while let Some(dir) = pending.pop() {
+		let Ok(entries) = std::fs::read_dir(&dir) else {
+			continue;
+		};
+		let mut subdirs = Vec::new();
+		let mut found = false;
+		for entry in entries.flatten() {
+			let path = entry.path();
+			if path.is_dir() {
+				subdirs.push(path);
+			} else if path.extension().is_some_and(|ext| ext == "uplugin") {
+				descriptors.push(path);
+				found = true;
+			}
+		}
+		if !found {
+			pending.append(&mut subdirs);
+		}
+	}
+	descriptors.sort();
+	descriptors
+}
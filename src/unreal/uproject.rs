@@ -2,21 +2,114 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::{types::Platform, utility::AsUnrealStr};
+use super::DiscoveredPlugin;
+use crate::{runner::SharedRunner, types::Platform, utility::AsUnrealStr};
 
+/// The contents of a `.uproject` file.
+///
+/// Fields which are not modeled are kept in `extra`, and the document that was read is kept
+/// so that writing the project back to disk does not lose any data or reorder hand-edited fields.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct UProject {
-	#[serde(rename = "EngineAssociation")]
+	#[serde(rename = "FileVersion", default)]
+	pub file_version: u32,
+	#[serde(rename = "EngineAssociation", default)]
 	pub engine_association: String,
-	#[serde(rename = "Plugins")]
-	plugins: Vec<Plugin>,
+	#[serde(rename = "Category", skip_serializing_if = "Option::is_none")]
+	pub category: Option<String>,
+	#[serde(rename = "Description", skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	#[serde(rename = "Modules", default, skip_serializing_if = "Vec::is_empty")]
+	pub modules: Vec<Module>,
+	#[serde(rename = "Plugins", default, skip_serializing_if = "Vec::is_empty")]
+	pub plugins: Vec<Plugin>,
+	#[serde(
+		rename = "TargetPlatforms",
+		default,
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub target_platforms: Vec<String>,
+	/// Directories, relative to the project root, that are searched for plugins in addition to `Plugins/`.
+	#[serde(
+		rename = "AdditionalPluginDirectories",
+		default,
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub additional_plugin_directories: Vec<PathBuf>,
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
+	#[serde(skip)]
+	format: DescriptorFormat,
+	#[serde(skip)]
+	document: Value,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct Plugin {
+/// A code module declared by a project or plugin.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Module {
+	#[serde(rename = "Name")]
+	pub name: String,
+	/// The kind of module (e.g. "Runtime", "Editor", "DeveloperTool").
+	#[serde(rename = "Type", default, skip_serializing_if = "String::is_empty")]
+	pub module_type: String,
+	/// When the module is loaded (e.g. "Default", "PreDefault", "PostEngineInit").
+	#[serde(
+		rename = "LoadingPhase",
+		default,
+		skip_serializing_if = "String::is_empty"
+	)]
+	pub loading_phase: String,
+	#[serde(
+		rename = "AdditionalDependencies",
+		default,
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub additional_dependencies: Vec<String>,
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
+}
+
+/// A reference to a plugin from a project's `Plugins` list.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Plugin {
 	#[serde(rename = "Name")]
 	pub name: String,
+	/// `None` if the entry does not set `Enabled`, which the engine treats as disabled.
+	#[serde(rename = "Enabled", default, skip_serializing_if = "Option::is_none")]
+	pub enabled: Option<bool>,
+	#[serde(rename = "MarketplaceURL", skip_serializing_if = "Option::is_none")]
+	pub marketplace_url: Option<String>,
+	/// If not empty, the plugin is only enabled for these target types (e.g. "Editor", "Game").
+	#[serde(
+		rename = "TargetAllowList",
+		default,
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub target_allow_list: Vec<String>,
+	/// If not empty, the plugin is only enabled for these platforms (e.g. "Win64", "Linux").
+	#[serde(
+		rename = "PlatformAllowList",
+		default,
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub platform_allow_list: Vec<String>,
+	/// The plugin is never enabled for these platforms.
+	#[serde(
+		rename = "PlatformDenyList",
+		default,
+		skip_serializing_if = "Vec::is_empty"
+	)]
+	pub platform_deny_list: Vec<String>,
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
+}
+
+impl Plugin {
+	pub fn is_enabled(&self) -> bool {
+		self.enabled.unwrap_or(false)
+	}
 }
 
 impl UProject {
@@ -24,7 +117,32 @@ impl UProject {
 		let json = tokio::fs::read_to_string(path)
 			.await
 			.context(format!("read {:?}", path))?;
-		Ok(serde_json::from_str::<Self>(&json)?)
+		Self::parse(&json)
+	}
+
+	fn parse(json: &str) -> anyhow::Result<Self> {
+		let document = serde_json::from_str::<Value>(json)?;
+		let mut project = Self::deserialize(&document)?;
+		project.format = DescriptorFormat::detect(json);
+		project.document = document;
+		Ok(project)
+	}
+
+	/// Writes the project to disk using the same tab-indented formatting as the editor.
+	pub async fn write(&self, path: &Path, runner: &SharedRunner) -> anyhow::Result<()> {
+		let json = self.to_json()?;
+		runner
+			.write_file(path.to_owned(), json.into_bytes())
+			.await
+			.context(format!("write {:?}", path))?;
+		Ok(())
+	}
+
+	/// Serializes the project into the document it was read from, so that fields keep their original order.
+	fn to_json(&self) -> anyhow::Result<String> {
+		let mut document = self.document.clone();
+		update_in_place(&mut document, serde_json::to_value(self)?);
+		self.format.serialize(&document)
 	}
 
 	pub fn get_engine_association(&self) -> Option<&String> {
@@ -34,18 +152,180 @@ impl UProject {
 		}
 	}
 
-	pub fn get_module_paths(&self, platform: Platform) -> Vec<PathBuf> {
-		let mut subpaths = vec![PathBuf::new()];
-		for plugin in &self.plugins {
-			subpaths.push(PathBuf::from(format!("Plugins/{name}", name = plugin.name)));
+	/// The paths of the editor's `.modules` files for the project and each of its plugins which declare code modules.
+	/// Paths for the project are relative to the project root; plugins use the locations they were discovered at.
+	pub fn get_module_paths(
+		&self,
+		platform: Platform,
+		editor_name: &str,
+		plugins: &[DiscoveredPlugin],
+	) -> Vec<PathBuf> {
+		let mut subpaths = Vec::new();
+		if !self.modules.is_empty() {
+			subpaths.push(PathBuf::new());
+		}
+		for plugin in plugins.iter() {
+			if !plugin.descriptor.modules.is_empty() {
+				subpaths.push(plugin.dir().to_owned());
+			}
 		}
 		let platform_str = platform.as_ue();
 		subpaths
 			.into_iter()
 			.map(|mut subpath| {
-				subpath.push(format!("Binaries/{platform_str}/UE4Editor.modules"));
+				subpath.push(format!("Binaries/{platform_str}/{editor_name}.modules"));
 				subpath
 			})
 			.collect()
 	}
 }
+
+/// Updates the document to the value without moving the fields which are in both.
+/// Fields which were removed are dropped, and new fields are added at the end.
+/// Objects in arrays (e.g. `Plugins`) are matched by their `Name`, or by their position if they have none.
+fn update_in_place(document: &mut Value, value: Value) {
+	match (document, value) {
+		(Value::Object(fields), Value::Object(mut values)) => {
+			fields.retain(|key, _| values.contains_key(key));
+			for (key, field) in fields.iter_mut() {
+				update_in_place(field, values.remove(key).unwrap());
+			}
+			fields.extend(values);
+		}
+		(Value::Array(elements), Value::Array(values)) => {
+			let originals = std::mem::take(elements);
+			for (index, value) in values.into_iter().enumerate() {
+				let original = match value.get("Name") {
+					Some(name) => originals
+						.iter()
+						.find(|original| original.get("Name") == Some(name)),
+					None => originals.get(index),
+				};
+				let mut element = original.cloned().unwrap_or_default();
+				update_in_place(&mut element, value);
+				elements.push(element);
+			}
+		}
+		(document, value) => *document = value,
+	}
+}
+
+/// The line endings of a descriptor file (e.g. `.uproject` or `.uplugin`) when it was read,
+/// so that writing it back does not introduce whitespace-only changes.
+#[derive(Default, Clone, Copy, Debug)]
+pub(crate) struct DescriptorFormat {
+	crlf: bool,
+	trailing_newline: bool,
+}
+
+impl DescriptorFormat {
+	pub fn detect(json: &str) -> Self {
+		Self {
+			crlf: json.contains("\r\n"),
+			trailing_newline: json.ends_with('\n'),
+		}
+	}
+
+	/// Serializes the descriptor with tab indentation, matching the editor's formatting.
+	pub fn serialize<T: Serialize>(&self, value: &T) -> anyhow::Result<String> {
+		let mut bytes = Vec::new();
+		let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+		let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+		value.serialize(&mut serializer)?;
+		let mut json = String::from_utf8(bytes)?;
+		if self.trailing_newline {
+			json.push('\n');
+		}
+		if self.crlf {
+			json = json.replace('\n', "\r\n");
+		}
+		Ok(json)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A project whose fields are not in the order they are declared, with fields that are not modeled in between.
+	const HAND_EDITED: &str = "{\r
+	\"EngineAssociation\": \"4.27\",\r
+	\"FileVersion\": 3,\r
+	\"Plugins\": [\r
+		{\r
+			\"Enabled\": true,\r
+			\"Name\": \"OnlineSubsystemSteam\",\r
+			\"SupportedTargetPlatforms\": [\r
+				\"Win64\"\r
+			],\r
+			\"PlatformAllowList\": [\r
+				\"Win64\"\r
+			]\r
+		}\r
+	],\r
+	\"Category\": \"\",\r
+	\"Modules\": [\r
+		{\r
+			\"LoadingPhase\": \"Default\",\r
+			\"Name\": \"MyGame\",\r
+			\"Type\": \"Runtime\"\r
+		}\r
+	],\r
+	\"Description\": \"\"\r
+}\r
+";
+
+	#[test]
+	fn round_trip_keeps_the_file_unchanged() {
+		let project = UProject::parse(HAND_EDITED).unwrap();
+		assert_eq!(project.to_json().unwrap(), HAND_EDITED);
+	}
+
+	#[test]
+	fn edits_keep_the_order_of_fields() {
+		let mut project = UProject::parse(HAND_EDITED).unwrap();
+		project.plugins[0]
+			.platform_allow_list
+			.push("Linux".to_owned());
+		project.plugins.push(Plugin {
+			name: "Paper2D".to_owned(),
+			enabled: Some(true),
+			..Plugin::default()
+		});
+		project.description = None;
+		project.target_platforms.push("Linux".to_owned());
+
+		let json = project.to_json().unwrap();
+		let expected = HAND_EDITED
+			.replace(
+				"\t\t\t\t\"Win64\"\r\n\t\t\t]\r\n\t\t}\r\n\t],",
+				"\t\t\t\t\"Win64\",\r\n\t\t\t\t\"Linux\"\r\n\t\t\t]\r\n\t\t},\r\n\t\t{\r\n\t\t\t\"Name\": \"Paper2D\",\r\n\t\t\t\"Enabled\": true\r\n\t\t}\r\n\t],",
+			)
+			.replace(
+				"\t],\r\n\t\"Description\": \"\"\r\n",
+				"\t],\r\n\t\"TargetPlatforms\": [\r\n\t\t\"Linux\"\r\n\t]\r\n",
+			);
+		assert_eq!(json, expected);
+	}
+
+	#[test]
+	fn plugins_without_enabled_stay_without_it() {
+		let json = "{\n\t\"FileVersion\": 3,\n\t\"EngineAssociation\": \"4.27\",\n\t\"Plugins\": [\n\t\t{\n\t\t\t\"Name\": \"Paper2D\",\n\t\t\t\"TargetAllowList\": [\n\t\t\t\t\"Editor\"\n\t\t\t]\n\t\t}\n\t]\n}";
+		let project = UProject::parse(json).unwrap();
+		assert_eq!(project.plugins[0].enabled, None);
+		assert!(!project.plugins[0].is_enabled());
+		assert_eq!(project.to_json().unwrap(), json);
+	}
+
+	#[test]
+	fn new_projects_use_the_declared_order() {
+		let project = UProject {
+			file_version: 3,
+			..UProject::default()
+		};
+		assert_eq!(
+			project.to_json().unwrap(),
+			"{\n\t\"FileVersion\": 3,\n\t\"EngineAssociation\": \"\"\n}"
+		);
+	}
+}
@@ -1,6 +1,16 @@
+use crate::{
+	diagnostics::{Diagnostic, DiagnosticCollector, DiagnosticSummary},
+	runner::{OutputStream, SharedProcessObserver},
+};
 use futures::Future;
-use std::pin::Pin;
+use std::{
+	collections::VecDeque,
+	pin::Pin,
+	process::ExitStatus,
+	sync::{Arc, Mutex},
+};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 
 // Alias used to represent a future that can be returned from a trait function,
 // because async is not supported for trait functions yet.
@@ -11,23 +21,94 @@ pub trait AsUnrealStr {
 	fn as_ue(&self) -> &'static str;
 }
 
+/// The current user's home directory, from `HOME` (or `USERPROFILE` on Windows).
+pub fn home_dir() -> Option<std::path::PathBuf> {
+	std::env::var_os("HOME")
+		.or_else(|| std::env::var_os("USERPROFILE"))
+		.filter(|path| !path.is_empty())
+		.map(std::path::PathBuf::from)
+}
+
+/// The number of output lines retained for [`ProcessFailed::tail_of_output`].
+const OUTPUT_TAIL_LENGTH: usize = 20;
+
+/// A child process exited with a non-zero status.
+#[derive(thiserror::Error, Debug)]
+pub struct ProcessFailed {
+	pub program: String,
+	pub args: Vec<String>,
+	pub status: ExitStatus,
+	/// The last lines the process wrote to its output and error streams.
+	pub tail_of_output: Vec<String>,
+	/// The warnings and errors found in the process's output.
+	pub diagnostics: Vec<Diagnostic>,
+}
+impl std::fmt::Display for ProcessFailed {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"\"{} {}\" failed with {}",
+			self.program,
+			self.args.join(" "),
+			self.status
+		)?;
+		if !self.tail_of_output.is_empty() {
+			write!(f, "; last output:")?;
+			for line in self.tail_of_output.iter() {
+				write!(f, "\n  {line}")?;
+			}
+		}
+		Ok(())
+	}
+}
+
 /// Spawns the command as a child process in a detached task.
 /// Output and Error streams are parsed as strings in real-time, and printed to program output in their own detached tasks.
 /// Ends when the child process and all stream readers are complete, returning the join-task results.
-pub async fn spawn_command(command: &mut tokio::process::Command) -> anyhow::Result<()> {
+/// Output is parsed for [diagnostics](Diagnostic), which are summarized once the process ends.
+/// The observers are notified when the process starts, of each line of output, and when it ends.
+/// Fails with [`ProcessFailed`] if the child exits with a non-zero status.
+///
+/// Runs in a `process` span naming the program and its pid, so that its output can be told apart
+/// from that of other processes running at the same time.
+pub async fn spawn_command(
+	command: &mut tokio::process::Command,
+	observers: Vec<SharedProcessObserver>,
+) -> anyhow::Result<()> {
+	let name = std::path::Path::new(command.as_std().get_program())
+		.file_name()
+		.unwrap_or(command.as_std().get_program())
+		.to_string_lossy()
+		.into_owned();
+	// Spans are filtered by level like events; at `ERROR` the output stays tagged however quiet the run is.
+	let span = tracing::error_span!("process", program = %name, pid = tracing::field::Empty);
+	run_command(command, observers).instrument(span).await
+}
+
+async fn run_command(
+	command: &mut tokio::process::Command,
+	observers: Vec<SharedProcessObserver>,
+) -> anyhow::Result<()> {
 	use anyhow::Context;
 	use std::process::Stdio;
 	use tokio::io::{AsyncBufReadExt, BufReader};
+	let program = command
+		.as_std()
+		.get_program()
+		.to_string_lossy()
+		.into_owned();
+	let args = command
+		.as_std()
+		.get_args()
+		.map(|os| os.to_string_lossy().into_owned())
+		.collect::<Vec<_>>();
+	let command_line = format!("{program} {}", args.join(" "));
 	{
-		let program = command.as_std().get_program().to_str().unwrap();
-		let args = command
-			.as_std()
-			.get_args()
-			.map(|os| os.to_str().unwrap())
-			.collect::<Vec<_>>()
-			.join(" ");
 		let dir = command.as_std().get_current_dir();
-		println!("Executing \"{program} {args}\" in {dir:?}");
+		tracing::info!("Executing \"{command_line}\" in {dir:?}");
+		for observer in observers.iter() {
+			observer.started(&command_line, dir);
+		}
 	}
 	let mut child = command
 		.stdin(Stdio::null())
@@ -35,45 +116,98 @@ pub async fn spawn_command(command: &mut tokio::process::Command) -> anyhow::Res
 		.stderr(Stdio::piped())
 		.spawn()
 		.context("failed to spawn process")?;
+	if let Some(pid) = child.id() {
+		tracing::Span::current().record("pid", pid);
+	}
 	let mut out_stream = BufReader::new(child.stdout.take().unwrap()).lines();
 	let mut err_stream = BufReader::new(child.stderr.take().unwrap()).lines();
 
+	let tail = Arc::new(Mutex::new(VecDeque::with_capacity(OUTPUT_TAIL_LENGTH)));
+	let diagnostics = Arc::new(DiagnosticCollector::default());
+
 	let mut child = KillChildOnDrop(child);
-	let out_handle: JoinHandle<anyhow::Result<()>> = tokio::task::spawn(async move {
-		'read: loop {
-			match out_stream.next_line().await {
-				Ok(Some(line)) => {
-					// would be better if we were using the `log` crate, but this is simpler for proof-of-concept
-					// could look like: log::info!(target: "generate-project-files", "{line}");
-					println!("{line}");
+	let out_tail = tail.clone();
+	let out_diagnostics = diagnostics.clone();
+	let out_observers = observers.clone();
+	let out_handle: JoinHandle<anyhow::Result<()>> = tokio::task::spawn(
+		async move {
+			'read: loop {
+				match out_stream.next_line().await {
+					Ok(Some(line)) => {
+						tracing::info!(target: "process::stdout", "{line}");
+						let diagnostic = out_diagnostics.push_line(&line);
+						for observer in out_observers.iter() {
+							observer.output(OutputStream::Stdout, &line, diagnostic.as_ref());
+						}
+						push_tail(&out_tail, line);
+					}
+					Ok(None) => break 'read,
+					_ => {}
 				}
-				Ok(None) => break 'read,
-				_ => {}
 			}
+			Ok(())
 		}
-		Ok(())
-	});
-	let err_handle: JoinHandle<anyhow::Result<()>> = tokio::task::spawn(async move {
-		'read: loop {
-			match err_stream.next_line().await {
-				Ok(Some(line)) => {
-					// would be better if we were using the `log` crate, but this is simpler for proof-of-concept
-					// could look like: log::error!(target: "generate-project-files", "{line}");
-					eprintln!("[ERROR] {line}");
+		.in_current_span(),
+	);
+	let err_tail = tail.clone();
+	let err_diagnostics = diagnostics.clone();
+	let err_observers = observers.clone();
+	let err_handle: JoinHandle<anyhow::Result<()>> = tokio::task::spawn(
+		async move {
+			'read: loop {
+				match err_stream.next_line().await {
+					Ok(Some(line)) => {
+						tracing::warn!(target: "process::stderr", "{line}");
+						let diagnostic = err_diagnostics.push_line(&line);
+						for observer in err_observers.iter() {
+							observer.output(OutputStream::Stderr, &line, diagnostic.as_ref());
+						}
+						push_tail(&err_tail, line);
+					}
+					Ok(None) => break 'read,
+					_ => {}
 				}
-				Ok(None) => break 'read,
-				_ => {}
 			}
+			Ok(())
 		}
-		Ok(())
-	});
+		.in_current_span(),
+	);
 
-	let _status = child.0.wait().await?;
+	let status = child.0.wait().await?;
 	out_handle.await?.context("pipe stdout")?;
-	err_handle.await?.context("pipe stdout")?;
+	err_handle.await?.context("pipe stderr")?;
+	// Both stream readers have finished, so this is the only reference left.
+	let diagnostics = Arc::into_inner(diagnostics).unwrap().into_inner();
+	if !diagnostics.is_empty() || !status.success() {
+		tracing::info!(
+			"\"{program}\" finished with {}",
+			DiagnosticSummary(&diagnostics)
+		);
+	}
+	for observer in observers.iter() {
+		observer.finished(&command_line, &status, &diagnostics)?;
+	}
+	if !status.success() {
+		let tail_of_output = tail.lock().unwrap().drain(..).collect();
+		return Err(ProcessFailed {
+			program,
+			args,
+			status,
+			tail_of_output,
+			diagnostics,
+		})?;
+	}
 	Ok(())
 }
 
+fn push_tail(tail: &Mutex<VecDeque<String>>, line: String) {
+	let mut tail = tail.lock().unwrap();
+	if tail.len() == OUTPUT_TAIL_LENGTH {
+		tail.pop_front();
+	}
+	tail.push_back(line);
+}
+
 // Wrapper for the child process to ensure that it kills the subprocess when dropped.
 struct KillChildOnDrop(tokio::process::Child);
 impl Drop for KillChildOnDrop {